@@ -0,0 +1,171 @@
+//! Benchmarks the generator and both parts of every day on the real puzzle inputs in
+//! `input/2024/dayN.txt` (`AOC_INPUT_DIR` overrides the directory), days without an input are
+//! skipped. Parts are measured on an already parsed input.
+//!
+//! Besides the usual criterion reports, a summary of the benchmarks that ran in this invocation is
+//! written to `<criterion home>/aoc-summary.json`.
+
+use std::{
+    fs,
+    hint::black_box,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use advent_of_code_2024::{
+    day01::Day01, day02::Day02, day03::Day03, day04::Day04, day05::Day05, day06::Day06,
+    day07::Day07, day08::Day08, day09::Day09, day10::Day10, day11::Day11, day12::Day12,
+    day13::Day13, day14::Day14, day15::Day15, day16::Day16, day17::Day17, day18::Day18,
+    day19::Day19, day20::Day20, day21::Day21, day22::Day22, day23::Day23, day24::Day24,
+    day25::Day25, solution::Solution,
+};
+use criterion::Criterion;
+use serde_json::{json, Value};
+
+const STEPS: [&str; 3] = ["parse", "part1", "part2"];
+
+fn input_dir() -> PathBuf {
+    std::env::var_os("AOC_INPUT_DIR").map_or_else(
+        || Path::new(env!("CARGO_MANIFEST_DIR")).join("input/2024"),
+        PathBuf::from,
+    )
+}
+
+fn criterion_home() -> PathBuf {
+    std::env::var_os("CRITERION_HOME").map_or_else(
+        || {
+            std::env::var_os("CARGO_TARGET_DIR")
+                .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+                .join("criterion")
+        },
+        PathBuf::from,
+    )
+}
+
+/// Benchmarks `S` if its input exists, returning whether it did.
+fn bench_day<S: Solution>(criterion: &mut Criterion, dir: &Path) -> bool {
+    let Ok(input) = fs::read_to_string(dir.join(format!("day{}.txt", S::DAY))) else {
+        return false;
+    };
+    let parsed = match S::parse(&input) {
+        Ok(parsed) => parsed,
+        Err(error) => {
+            eprintln!("Skipping day {}: {error}", S::DAY);
+            return false;
+        }
+    };
+
+    let mut group = criterion.benchmark_group(format!("day{:02}", S::DAY));
+    group.sample_size(10);
+    group.bench_function("parse", |b| b.iter(|| S::parse(black_box(&input))));
+    group.bench_function("part1", |b| b.iter(|| S::part1(black_box(&parsed))));
+    group.bench_function("part2", |b| b.iter(|| S::part2(black_box(&parsed))));
+    group.finish();
+
+    true
+}
+
+fn estimate(estimates: &Value, name: &str) -> Value {
+    estimates[name]["point_estimate"].clone()
+}
+
+/// Collects the estimates criterion saved since `started` for the benchmarks of `days` into one
+/// document.
+fn summary(home: &Path, days: &[u8], started: SystemTime) -> Value {
+    let mut benchmarks = Vec::new();
+    for &day in days {
+        for step in STEPS {
+            let path = home.join(format!("day{day:02}/{step}/new/estimates.json"));
+            // filtered out on the command line, the estimates are from an earlier run if any
+            let ran = fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified >= started);
+            if !ran {
+                continue;
+            }
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let estimates: Value = match serde_json::from_str(&content) {
+                Ok(estimates) => estimates,
+                Err(error) => {
+                    eprintln!("Could not read {}: {error}", path.display());
+                    continue;
+                }
+            };
+
+            benchmarks.push(json!({
+                "day": day,
+                "step": step,
+                "mean_ns": estimate(&estimates, "mean"),
+                "median_ns": estimate(&estimates, "median"),
+                "std_dev_ns": estimate(&estimates, "std_dev"),
+            }));
+        }
+    }
+
+    json!({ "benchmarks": benchmarks })
+}
+
+macro_rules! bench_days {
+    ($criterion:expr, $dir:expr, $($day:ident),* $(,)?) => {{
+        let mut days = Vec::new();
+        $(
+            if bench_day::<$day>($criterion, $dir) {
+                days.push(<$day as Solution>::DAY);
+            }
+        )*
+        days
+    }};
+}
+
+fn main() {
+    let started = SystemTime::now();
+    let mut criterion = Criterion::default().configure_from_args();
+    let dir = input_dir();
+
+    let days = bench_days!(
+        &mut criterion,
+        &dir,
+        Day01,
+        Day02,
+        Day03,
+        Day04,
+        Day05,
+        Day06,
+        Day07,
+        Day08,
+        Day09,
+        Day10,
+        Day11,
+        Day12,
+        Day13,
+        Day14,
+        Day15,
+        Day16,
+        Day17,
+        Day18,
+        Day19,
+        Day20,
+        Day21,
+        Day22,
+        Day23,
+        Day24,
+        Day25,
+    );
+    criterion.final_summary();
+
+    if days.is_empty() {
+        eprintln!("No inputs found in {}", dir.display());
+        return;
+    }
+
+    let home = criterion_home();
+    let path = home.join("aoc-summary.json");
+    let summary =
+        serde_json::to_string_pretty(&summary(&home, &days, started)).expect("Valid JSON");
+    match fs::write(&path, summary) {
+        Ok(()) => println!("Wrote {}", path.display()),
+        Err(error) => eprintln!("Could not write {}: {error}", path.display()),
+    }
+}
@@ -0,0 +1,312 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use advent_of_code_2024::{
+    day17::{self, Day17, Machine},
+    runner::{self, Solver},
+    solution::Solution,
+};
+
+const USAGE: &str = "Usage:
+    aoc2024 run --day <DAY> [--part <PART>] [--input <FILE>] [--param <KEY=VALUE>]...
+    aoc2024 run --all [--input-dir <DIR>]
+    aoc2024 disassemble [--input <FILE>] [--trace]
+
+Without --input (or with --input -) the input is read from stdin.
+--all reads <DIR>/day<DAY>.txt for every day, <DIR> defaults to input/2024.
+--param overrides a puzzle parameter, e.g. --param size=7x7 for day 18.
+disassemble prints the program of a day 17 input, --trace also runs it and prints the
+registers before every instruction.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Run {
+        day: u8,
+        part: Option<u8>,
+        input: Option<PathBuf>,
+        parameters: Vec<String>,
+    },
+    RunAll {
+        input_dir: PathBuf,
+    },
+    Disassemble {
+        input: Option<PathBuf>,
+        trace: bool,
+    },
+    Help,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    match args.next().as_deref() {
+        Some("run") => (),
+        Some("disassemble") => return parse_disassemble_args(args),
+        Some("help" | "--help" | "-h") | None => return Ok(Command::Help),
+        Some(other) => return Err(format!("Unknown command {other:?}")),
+    }
+
+    let mut day = None;
+    let mut part = None;
+    let mut input = None;
+    let mut input_dir = None;
+    let mut parameters = Vec::new();
+    let mut all = false;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("Missing value after {arg}"));
+        match arg.as_str() {
+            "--day" => day = Some(value()?),
+            "--part" => part = Some(value()?),
+            "--input" => input = Some(value()?),
+            "--input-dir" => input_dir = Some(value()?),
+            "--param" => parameters.push(value()?),
+            "--all" => all = true,
+            "--help" | "-h" => return Ok(Command::Help),
+            other => return Err(format!("Unknown argument {other:?}")),
+        }
+    }
+
+    let parse_number = |name: &str, value: String, max: u8| {
+        value
+            .parse::<u8>()
+            .ok()
+            .filter(|number| (1..=max).contains(number))
+            .ok_or(format!(
+                "--{name} must be between 1 and {max}, got {value:?}"
+            ))
+    };
+
+    if all {
+        if day.is_some() || part.is_some() || input.is_some() || !parameters.is_empty() {
+            return Err(
+                "--all cannot be combined with --day, --part, --input or --param".to_owned(),
+            );
+        }
+
+        return Ok(Command::RunAll {
+            input_dir: input_dir.map_or_else(|| PathBuf::from("input/2024"), PathBuf::from),
+        });
+    }
+
+    if input_dir.is_some() {
+        return Err("--input-dir is only used with --all".to_owned());
+    }
+
+    Ok(Command::Run {
+        day: parse_number("day", day.ok_or("Missing --day or --all")?, 25)?,
+        part: part.map(|part| parse_number("part", part, 2)).transpose()?,
+        input: input.filter(|input| input != "-").map(PathBuf::from),
+        parameters,
+    })
+}
+
+fn parse_disassemble_args(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut input = None;
+    let mut trace = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => input = Some(args.next().ok_or("Missing value after --input")?),
+            "--trace" => trace = true,
+            "--help" | "-h" => return Ok(Command::Help),
+            other => return Err(format!("Unknown argument {other:?}")),
+        }
+    }
+
+    Ok(Command::Disassemble {
+        input: input.filter(|input| input != "-").map(PathBuf::from),
+        trace,
+    })
+}
+
+fn read_input(path: Option<&Path>) -> Result<String, String> {
+    match path {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|error| format!("Could not read {}: {error}", path.display())),
+        None => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|error| format!("Could not read stdin: {error}"))?;
+            Ok(input)
+        }
+    }
+}
+
+/// Prints the answer and timings of `solver`, returns false if it failed.
+fn run_solver(solver: &Solver, input: &str, parameters: &[String]) -> bool {
+    match solver.run_with_parameters(input, parameters) {
+        Ok(outcome) => {
+            println!(
+                "Day {} - Part {}: {}",
+                solver.day, solver.part, outcome.answer
+            );
+            println!(
+                "\tparse: {:?}, solve: {:?}",
+                outcome.parse_time, outcome.solve_time
+            );
+            true
+        }
+        Err(error) => {
+            eprintln!("Day {} - Part {}: {error}", solver.day, solver.part);
+            false
+        }
+    }
+}
+
+/// Prints the disassembled day 17 program and, with `trace`, every step of running it.
+fn disassemble(input: &str, trace: bool) -> Result<(), String> {
+    let (program, registers) = Day17::parse(input).map_err(|error| error.to_string())?;
+    print!("{}", day17::disassemble(&program));
+    if !trace {
+        return Ok(());
+    }
+
+    println!();
+    let mut machine = Machine::new(&program, registers);
+    let output = machine
+        .run_traced(None, |entry| println!("{entry}"))
+        .map_err(|error| error.to_string())?;
+    println!(
+        "Output: {}",
+        output
+            .iter()
+            .map(u8::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut success = true;
+    match command {
+        Command::Help => println!("{USAGE}"),
+        Command::Run {
+            day,
+            part,
+            input,
+            parameters,
+        } => {
+            let input = match read_input(input.as_deref()) {
+                Ok(input) => input,
+                Err(error) => {
+                    eprintln!("{error}");
+                    return ExitCode::FAILURE;
+                }
+            };
+
+            let solvers = runner::solvers_for_day(day)
+                .filter(|solver| part.is_none_or(|part| solver.part == part))
+                .collect::<Vec<_>>();
+            if solvers.is_empty() {
+                eprintln!("No solver for day {day} part {}", part.unwrap_or(1));
+                return ExitCode::FAILURE;
+            }
+
+            for solver in solvers {
+                success &= run_solver(solver, &input, &parameters);
+            }
+        }
+        Command::RunAll { input_dir } => {
+            for day in 1..=25 {
+                let path = input_dir.join(format!("day{day}.txt"));
+                let Ok(input) = std::fs::read_to_string(&path) else {
+                    eprintln!("Day {day}: skipped, could not read {}", path.display());
+                    continue;
+                };
+
+                for solver in runner::solvers_for_day(day) {
+                    success &= run_solver(solver, &input, &[]);
+                }
+            }
+        }
+        Command::Disassemble { input, trace } => {
+            if let Err(error) =
+                read_input(input.as_deref()).and_then(|input| disassemble(&input, trace))
+            {
+                eprintln!("{error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if success {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &str) -> impl Iterator<Item = String> + '_ {
+        args.split_whitespace().map(str::to_owned)
+    }
+
+    #[test]
+    fn parse_run_args() {
+        assert_eq!(
+            parse_args(args("run --day 16 --part 2 --input day16.txt")),
+            Ok(Command::Run {
+                day: 16,
+                part: Some(2),
+                input: Some(PathBuf::from("day16.txt")),
+                parameters: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse_args(args("run --day 3 --input -")),
+            Ok(Command::Run {
+                day: 3,
+                part: None,
+                input: None,
+                parameters: Vec::new(),
+            })
+        );
+        assert_eq!(
+            parse_args(args("run --day 18 --param size=7x7 --param bytes=12")),
+            Ok(Command::Run {
+                day: 18,
+                part: None,
+                input: None,
+                parameters: vec!["size=7x7".to_owned(), "bytes=12".to_owned()],
+            })
+        );
+        assert_eq!(
+            parse_args(args("run --all")),
+            Ok(Command::RunAll {
+                input_dir: PathBuf::from("input/2024")
+            })
+        );
+        assert_eq!(
+            parse_args(args("disassemble --input day17.txt --trace")),
+            Ok(Command::Disassemble {
+                input: Some(PathBuf::from("day17.txt")),
+                trace: true
+            })
+        );
+        assert_eq!(parse_args(args("")), Ok(Command::Help));
+    }
+
+    #[test]
+    fn invalid_args() {
+        assert!(parse_args(args("run --day 26")).is_err());
+        assert!(parse_args(args("run --day 1 --part 3")).is_err());
+        assert!(parse_args(args("run --day")).is_err());
+        assert!(parse_args(args("run --all --day 2")).is_err());
+        assert!(parse_args(args("run --all --param size=7x7")).is_err());
+        assert!(parse_args(args("walk")).is_err());
+        assert!(parse_args(args("disassemble --day 17")).is_err());
+    }
+}
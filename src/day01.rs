@@ -3,17 +3,33 @@ use std::vec;
 use fnv::FnvHashMap;
 use itertools::zip_eq;
 
-#[aoc(day01, part1)]
-fn part1(input: &str) -> u64 {
-    let mut left: Vec<u64> = vec![];
-    let mut right: Vec<u64> = vec![];
-
-    for line in input.lines() {
-        let (l, r) = line.split_once(' ').expect("Could not split line");
-        left.push(l.trim().parse().expect("Could not parse left num"));
-        right.push(r.trim().parse().expect("Could not parse right num"));
+use crate::{parse::ParseError, solution::Solution};
+
+#[aoc_generator(day01)]
+fn parse(input: &str) -> Result<(Vec<u64>, Vec<u64>), ParseError> {
+    let mut left = vec![];
+    let mut right = vec![];
+
+    for line in input.lines().filter(|line| !line.is_empty()) {
+        let mut numbers = line.split_whitespace().map(|number| {
+            number
+                .parse::<u64>()
+                .map_err(|error| ParseError::at(1, input, number, error))
+        });
+        let (Some(l), Some(r), None) = (numbers.next(), numbers.next(), numbers.next()) else {
+            return Err(ParseError::at(1, input, line, "Expected two location ids"));
+        };
+        left.push(l?);
+        right.push(r?);
     }
 
+    Ok((left, right))
+}
+
+#[aoc(day01, part1)]
+fn part1((left, right): &(Vec<u64>, Vec<u64>)) -> u64 {
+    let mut left = left.clone();
+    let mut right = right.clone();
     left.sort();
     right.sort();
     zip_eq(left, right)
@@ -30,24 +46,39 @@ fn add_to_hash_map(num: u64, map: &mut FnvHashMap<u64, u64>) {
 }
 
 #[aoc(day01, part2)]
-fn part2(input: &str) -> u64 {
-    let mut left = vec![];
+fn part2((left, right_list): &(Vec<u64>, Vec<u64>)) -> u64 {
     let mut right = FnvHashMap::default();
-
-    for line in input.lines() {
-        let (l, r) = line.split_once(' ').expect("Could not split line");
-        left.push(l.trim().parse().expect("Could not parse left num"));
-        add_to_hash_map(
-            r.trim().parse().expect("Could not parse right num"),
-            &mut right,
-        );
+    for &num in right_list {
+        add_to_hash_map(num, &mut right);
     }
 
-    left.into_iter()
-        .map(|num| right.get(&num).unwrap_or(&0) * num)
+    left.iter()
+        .map(|num| right.get(num).unwrap_or(&0) * num)
         .sum()
 }
 
+pub struct Day01;
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+
+    type Input<'i> = (Vec<u64>, Vec<u64>);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,11 +92,23 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(EXAMPLE), 11);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 11);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(EXAMPLE), 31);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 31);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("3   4\n1 x").unwrap_err().to_string(),
+            "Day 01, line 2, column 3: invalid digit found in string"
+        );
+        assert_eq!(
+            parse("3   4\n1").unwrap_err().to_string(),
+            "Day 01, line 2, column 1: Expected two location ids"
+        );
     }
 }
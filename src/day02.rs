@@ -1,16 +1,21 @@
 use itertools::Itertools;
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[aoc_generator(day02)]
-fn parse(input: &str) -> Vec<Vec<i64>> {
+fn parse(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
     input
         .lines()
         .filter(|line| !line.is_empty())
         .map(|line| {
             line.split_whitespace()
-                .map(|part| part.parse::<i64>().expect("Could not parse int"))
-                .collect_vec()
+                .map(|part| {
+                    part.parse::<i64>()
+                        .map_err(|error| ParseError::at(2, input, part, error))
+                })
+                .try_collect()
         })
-        .collect_vec()
+        .try_collect()
 }
 
 fn is_safe_report(report: &[i64]) -> bool {
@@ -80,6 +85,28 @@ fn part2(input: &[Vec<i64>]) -> usize {
         .count()
 }
 
+pub struct Day02;
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+
+    type Input<'i> = Vec<Vec<i64>>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,11 +120,11 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 2);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 2);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE)), 4);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 4);
     }
 }
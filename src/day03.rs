@@ -1,68 +1,90 @@
 use regex::Regex;
 
-#[aoc(day03, part1)]
-fn part1(input: &str) -> u64 {
-    let regex = Regex::new(r#"mul\((\d+),(\d+)\)"#).expect("Could not compile regex");
-    regex
-        .captures_iter(input)
-        .map(|m| {
-            let left: u64 = m
-                .get(1)
-                .expect("Capture group 1 missing")
-                .as_str()
-                .parse()
-                .expect("Failed to parse int");
-            let right: u64 = m
-                .get(2)
-                .expect("Capture group 2 missing")
-                .as_str()
-                .parse()
-                .expect("Failed to parse int");
-            left * right
-        })
-        .sum()
+use crate::{parse::ParseError, solution::Solution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Mul(u64, u64),
+    Do,
+    Dont,
 }
 
-#[aoc(day03, part2)]
-fn part2(input: &str) -> u64 {
+/// Picks the instructions out of the corrupted memory, everything else is ignored.
+#[aoc_generator(day03)]
+fn parse(input: &str) -> Result<Vec<Instruction>, ParseError> {
     let regex =
         Regex::new(r#"mul\((\d+),(\d+)\)|do\(\)|don't\(\)"#).expect("Could not compile regex");
-    let mut enabled = true;
     regex
         .captures_iter(input)
-        .map(|m| {
-            let whole = m.get(0).unwrap().as_str();
-            if whole == "don't()" {
-                enabled = false;
-                return 0;
+        .map(|m| match (m.get(1), m.get(2)) {
+            (Some(left), Some(right)) => {
+                let parse_number = |number: regex::Match<'_>| {
+                    number
+                        .as_str()
+                        .parse()
+                        .map_err(|error| ParseError::at(3, input, number.as_str(), error))
+                };
+                Ok(Instruction::Mul(parse_number(left)?, parse_number(right)?))
             }
+            _ if &m[0] == "do()" => Ok(Instruction::Do),
+            _ => Ok(Instruction::Dont),
+        })
+        .collect()
+}
+
+#[aoc(day03, part1)]
+fn part1(instructions: &[Instruction]) -> u64 {
+    instructions
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::Mul(left, right) => left * right,
+            Instruction::Do | Instruction::Dont => 0,
+        })
+        .sum()
+}
 
-            if whole == "do()" {
+#[aoc(day03, part2)]
+fn part2(instructions: &[Instruction]) -> u64 {
+    let mut enabled = true;
+    instructions
+        .iter()
+        .map(|instruction| match instruction {
+            Instruction::Mul(left, right) if enabled => left * right,
+            Instruction::Mul(..) => 0,
+            Instruction::Do => {
                 enabled = true;
-                return 0;
+                0
             }
-
-            if enabled {
-                let left: u64 = m
-                    .get(1)
-                    .expect("Capture group 1 missing")
-                    .as_str()
-                    .parse()
-                    .expect("Failed to parse int");
-                let right: u64 = m
-                    .get(2)
-                    .expect("Capture group 2 missing")
-                    .as_str()
-                    .parse()
-                    .expect("Failed to parse int");
-                left * right
-            } else {
+            Instruction::Dont => {
+                enabled = false;
                 0
             }
         })
         .sum()
 }
 
+pub struct Day03;
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+
+    type Input<'i> = Vec<Instruction>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,11 +96,21 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(PART1_EXAMPLE), 161);
+        assert_eq!(part1(&parse(PART1_EXAMPLE).unwrap()), 161);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(PART2_EXAMPLE), 48);
+        assert_eq!(part2(&parse(PART2_EXAMPLE).unwrap()), 48);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("mul(2,4)\nxmul(1,99999999999999999999)")
+                .unwrap_err()
+                .to_string(),
+            "Day 03, line 2, column 8: number too large to fit in target type"
+        );
     }
 }
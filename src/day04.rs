@@ -1,5 +1,7 @@
 use itertools::Itertools;
 
+use crate::{grid::Grid, parse::ParseError, solution::Solution};
+
 fn count_xmas(stream: impl IntoIterator<Item = u8>) -> usize {
     const XMAS: &[u8] = b"XMAS";
     let mut current = 0;
@@ -110,9 +112,19 @@ fn count_diagonals_down_left(matrix: &[&[u8]]) -> usize {
     count
 }
 
+#[aoc_generator(day04)]
+fn parse(input: &str) -> Result<Grid<u8>, ParseError> {
+    let grid = Grid::parse(input).map_err(|error| ParseError::from_grid(4, error))?;
+    if grid.height() == 0 {
+        return Err(ParseError::at_end(4, input, "Word search is empty"));
+    }
+
+    Ok(grid)
+}
+
 #[aoc(day04, part1)]
-fn part1(input: &str) -> usize {
-    let input = input.lines().map(str::as_bytes).collect_vec();
+fn part1(grid: &Grid<u8>) -> usize {
+    let input = grid.rows().collect_vec();
 
     count_horizontals(&input)
         + count_verticals(&input)
@@ -159,10 +171,10 @@ fn is_x_mas(matrix: &[&[u8]], center: (usize, usize)) -> bool {
 }
 
 #[aoc(day04, part2)]
-fn part2(input: &str) -> usize {
-    let input = input.lines().map(str::as_bytes).collect_vec();
-    let col_length = input.len() - 1;
-    let row_length = input[0].len() - 1;
+fn part2(grid: &Grid<u8>) -> usize {
+    let input = grid.rows().collect_vec();
+    let col_length = input.len().saturating_sub(1);
+    let row_length = input[0].len().saturating_sub(1);
 
     (1..col_length)
         .map(|row_idx| {
@@ -173,6 +185,28 @@ fn part2(input: &str) -> usize {
         .sum()
 }
 
+pub struct Day04;
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+
+    type Input<'i> = Grid<u8>;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,11 +224,20 @@ MXMXAXMASX";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(EXAMPLE), 18);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 18);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(EXAMPLE), 9);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 9);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("XMAS\nSAM").unwrap_err().to_string(),
+            "Day 04, line 2, column 1: Row 1 has 3 cells, expected 4 like the first row"
+        );
+        assert!(parse("\n").is_err());
     }
 }
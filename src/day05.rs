@@ -1,27 +1,35 @@
 use fnv::{FnvHashMap, FnvHashSet};
+use itertools::Itertools;
+
+use crate::{parse::ParseError, solution::Solution};
+
+pub type Rules = FnvHashMap<u64, FnvHashSet<u64>>;
 
 #[aoc_generator(day05)]
-fn parse(input: &str) -> (FnvHashMap<u64, FnvHashSet<u64>>, Vec<Vec<u64>>) {
+fn parse(input: &str) -> Result<(Rules, Vec<Vec<u64>>), ParseError> {
+    let parse_num = |num: &str| {
+        num.parse::<u64>()
+            .map_err(|error| ParseError::at(5, input, num, error))
+    };
+
     let mut map = FnvHashMap::default();
     let mut lines = input.lines();
     for line in lines.by_ref().take_while(|&line| !line.is_empty()) {
-        let (left, right) = line.split_once('|').expect("Expected '|' in mapping");
+        let (left, right) = line
+            .split_once('|')
+            .ok_or_else(|| ParseError::at(5, input, line, "Expected '|' in mapping"))?;
         let entry = map
-            .entry(right.parse().expect("Failed to parse int"))
+            .entry(parse_num(right)?)
             .or_insert_with(FnvHashSet::default);
-        entry.insert(left.parse().expect("Failed to parse int"));
+        entry.insert(parse_num(left)?);
     }
 
-    (
+    Ok((
         map,
         lines
-            .map(|line| {
-                line.split(',')
-                    .map(|num| num.parse().expect("Failed to parse int"))
-                    .collect()
-            })
-            .collect(),
-    )
+            .map(|line| line.split(',').map(parse_num).try_collect())
+            .try_collect()?,
+    ))
 }
 
 fn is_valid_print_order(order: &[u64], mapping: &FnvHashMap<u64, FnvHashSet<u64>>) -> bool {
@@ -99,6 +107,28 @@ fn part2(input: &(FnvHashMap<u64, FnvHashSet<u64>>, Vec<Vec<u64>>)) -> u64 {
         .sum()
 }
 
+pub struct Day05;
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+
+    type Input<'i> = (Rules, Vec<Vec<u64>>);
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -134,11 +164,11 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 143);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 143);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE)), 123);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 123);
     }
 }
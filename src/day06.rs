@@ -1,19 +1,26 @@
 use fnv::{FnvHashMap, FnvHashSet};
-use itertools::Itertools;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
+use crate::{
+    grid::{Grid, Position},
+    parse::ParseError,
+    solution::Solution,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Tile {
     Walkable,
     Obstacle,
 }
 
-impl From<char> for Tile {
-    fn from(value: char) -> Self {
+impl TryFrom<u8> for Tile {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            '.' | '^' => Self::Walkable,
-            '#' => Self::Obstacle,
-            _ => panic!("Invalid tile: {value:?}"),
+            b'.' | b'^' => Ok(Self::Walkable),
+            b'#' => Ok(Self::Obstacle),
+            _ => Err(format!("Invalid tile: {:?}", value as char)),
         }
     }
 }
@@ -36,50 +43,39 @@ impl Direction {
         };
     }
 
-    fn translate_pos(self, position: Position) -> Option<Position> {
-        Some(match self {
-            Self::Up => (position.0.checked_sub(1)?, position.1),
-            Self::Down => (position.0 + 1, position.1),
-            Self::Left => (position.0, position.1.checked_sub(1)?),
-            Self::Right => (position.0, position.1 + 1),
-        })
+    fn translate_pos(self, position: Position) -> Position {
+        match self {
+            Self::Up => Position(position.0 - 1, position.1),
+            Self::Down => Position(position.0 + 1, position.1),
+            Self::Left => Position(position.0, position.1 - 1),
+            Self::Right => Position(position.0, position.1 + 1),
+        }
     }
 }
 
-type Position = (usize, usize);
-
 #[derive(Debug, Clone)]
-struct MappedArea {
-    map: Vec<Vec<Tile>>,
+pub struct MappedArea {
+    map: Grid<Tile>,
     guard_start_pos: Position,
 }
 
 #[aoc_generator(day06)]
-fn parse(input: &str) -> MappedArea {
-    let mut guard_start_pos = (0, 0);
-    let map = input
-        .lines()
-        .filter(|&line| !line.is_empty())
-        .enumerate()
-        .map(|(row, line)| {
-            line.trim()
-                .chars()
-                .enumerate()
-                .map(|(col, c)| {
-                    if c == '^' {
-                        guard_start_pos = (row, col);
-                    }
-
-                    Tile::from(c)
-                })
-                .collect_vec()
-        })
-        .collect_vec();
+fn parse(input: &str) -> Result<MappedArea, ParseError> {
+    let mut guard_start_pos = None;
+    let map = Grid::parse_with(input, |position, c| {
+        if c == b'^' {
+            guard_start_pos = Some(position);
+        }
+
+        Tile::try_from(c)
+    })
+    .map_err(|error| ParseError::from_grid(6, error))?;
 
-    MappedArea {
+    Ok(MappedArea {
         map,
-        guard_start_pos,
-    }
+        guard_start_pos: guard_start_pos
+            .ok_or_else(|| ParseError::at_end(6, input, "Did not find the guard '^'"))?,
+    })
 }
 
 fn perform_walk(map: &MappedArea) -> (FnvHashSet<Position>, bool) {
@@ -89,10 +85,8 @@ fn perform_walk(map: &MappedArea) -> (FnvHashSet<Position>, bool) {
         FnvHashMap::from_iter(std::iter::once((current_position, vec![guard_direction])));
 
     let looping = loop {
-        match guard_direction
-            .translate_pos(current_position)
-            .and_then(|new_pos| Some((new_pos, *map.map.get(new_pos.0)?.get(new_pos.1)?)))
-        {
+        let new_pos = guard_direction.translate_pos(current_position);
+        match map.map.get(new_pos).map(|&tile| (new_pos, tile)) {
             None => {
                 break false;
             }
@@ -137,7 +131,7 @@ fn part2(input: &MappedArea) -> usize {
         .into_par_iter()
         .filter(|&position| {
             let mut map = input.clone();
-            map.map[position.0][position.1] = Tile::Obstacle;
+            map.map[position] = Tile::Obstacle;
 
             let (_, looping) = perform_walk(&map);
             looping
@@ -145,6 +139,28 @@ fn part2(input: &MappedArea) -> usize {
         .count()
 }
 
+pub struct Day06;
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+
+    type Input<'i> = MappedArea;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,11 +178,11 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 41);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 41);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE)), 6);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 6);
     }
 }
@@ -3,6 +3,8 @@ use std::{error::Error, str::FromStr};
 use itertools::Itertools;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Operator {
     Add,
@@ -47,7 +49,7 @@ impl From<Operator> for char {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Equation {
+pub struct Equation {
     result: u64,
     terms: Vec<u64>,
 }
@@ -69,11 +71,14 @@ impl FromStr for Equation {
 }
 
 #[aoc_generator(day07)]
-fn parse(input: &str) -> Vec<Equation> {
+fn parse(input: &str) -> Result<Vec<Equation>, ParseError> {
     input
         .lines()
-        .map(|line| line.parse().expect("Could not parse equation"))
-        .collect_vec()
+        .map(|line| {
+            line.parse()
+                .map_err(|error| ParseError::at(7, input, line, error))
+        })
+        .try_collect()
 }
 
 fn check_with_operators(ops: &[Operator], equation: &Equation) -> bool {
@@ -172,6 +177,28 @@ fn part2(equations: &[Equation]) -> u64 {
         .sum()
 }
 
+pub struct Day07;
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+
+    type Input<'i> = Vec<Equation>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,11 +215,11 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 3749);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 3749);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(EXAMPLE)), 11387);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 11387);
     }
 }
@@ -1,6 +1,8 @@
 use fnv::{FnvHashMap, FnvHashSet};
 use itertools::Itertools;
 
+use crate::{parse::ParseError, solution::Solution};
+
 type Position = (isize, isize);
 
 fn calculate_antinode_positions(a: Position, b: Position) -> (Position, Position) {
@@ -14,7 +16,7 @@ fn calculate_antinode_positions(a: Position, b: Position) -> (Position, Position
 }
 
 #[derive(Debug, Clone)]
-struct Map {
+pub struct Map {
     antennas: FnvHashMap<char, FnvHashSet<Position>>,
     grid_height: usize,
     grid_width: usize,
@@ -31,7 +33,7 @@ impl Map {
 }
 
 #[aoc_generator(day08)]
-fn parse(input: &str) -> Map {
+fn parse(input: &str) -> Result<Map, ParseError> {
     let mut antennas = FnvHashMap::default();
 
     for (row, line) in input.lines().enumerate() {
@@ -44,11 +46,16 @@ fn parse(input: &str) -> Map {
         }
     }
 
-    Map {
+    let first_line = input
+        .lines()
+        .next()
+        .ok_or_else(|| ParseError::new(8, 1, 1, "Map is empty"))?;
+
+    Ok(Map {
         antennas,
         grid_height: input.lines().count(),
-        grid_width: input.lines().next().unwrap().chars().count(),
-    }
+        grid_width: first_line.chars().count(),
+    })
 }
 
 fn part1_do_freq(
@@ -120,6 +127,28 @@ fn part2(map: &Map) -> usize {
     antinodes.len()
 }
 
+pub struct Day08;
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+
+    type Input<'i> = Map;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -139,12 +168,12 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 14);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 14);
     }
 
     #[test]
     fn part2_example1() {
-        assert_eq!(part2(&parse(EXAMPLE)), 34);
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 34);
     }
 
     const PART2_EXAMPLE: &str = "T....#....
@@ -160,6 +189,6 @@ mod tests {
 
     #[test]
     fn part2_example2() {
-        assert_eq!(part2(&parse(PART2_EXAMPLE)), 9);
+        assert_eq!(part2(&parse(PART2_EXAMPLE).unwrap()), 9);
     }
 }
@@ -1,3 +1,10 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
+};
+
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct FSEntryInner {
     file_id: u64,
@@ -10,8 +17,13 @@ enum FSEntry {
     File(FSEntryInner),
 }
 
+/// First block and file.
+type PlacedFile = (usize, FSEntryInner);
+/// First block and length.
+type FreeSpan = (usize, usize);
+
 #[derive(Debug, Clone)]
-struct FSMap {
+pub struct FSMap {
     fs: Vec<FSEntry>,
     left_most_free_space: usize,
 }
@@ -37,24 +49,149 @@ impl FSMap {
             .sum()
     }
 
-    fn shift_into_free_space_whole(&mut self) {
-        // println!("{}", self);
-        let mut current_file = self.fs.len() - 1;
-        while current_file > self.left_most_free_space {
-            let FSEntry::File(entry) = self.fs[current_file] else {
-                current_file -= 1;
+    /// Files and free spans in order, plus the total number of blocks. Empty files are left out,
+    /// so free spaces around them are merged.
+    fn blocks(&self) -> (Vec<PlacedFile>, Vec<FreeSpan>, usize) {
+        let mut files = Vec::new();
+        let mut free_spans: Vec<FreeSpan> = Vec::new();
+        let mut position = 0;
+        for entry in &self.fs {
+            match *entry {
+                FSEntry::File(file) if file.length == 0 => (),
+                FSEntry::File(file) => {
+                    files.push((position, file));
+                    position += file.length;
+                }
+                FSEntry::FreeSpace(0) => (),
+                FSEntry::FreeSpace(length) => {
+                    match free_spans.last_mut() {
+                        Some((start, previous)) if *start + *previous == position => {
+                            *previous += length
+                        }
+                        _ => free_spans.push((position, length)),
+                    }
+                    position += length;
+                }
+            }
+        }
+
+        (files, free_spans, position)
+    }
+
+    /// Replaces the layout by `files`, with free space in between and up to `total_blocks`.
+    fn rebuild(&mut self, mut files: Vec<PlacedFile>, total_blocks: usize) {
+        files.sort_unstable_by_key(|&(start, _)| start);
+
+        let mut fs = Vec::with_capacity(files.len() * 2 + 1);
+        let mut position = 0;
+        for (start, file) in files {
+            if start > position {
+                fs.push(FSEntry::FreeSpace(start - position));
+            }
+
+            fs.push(FSEntry::File(file));
+            position = start + file.length;
+        }
+
+        if total_blocks > position {
+            fs.push(FSEntry::FreeSpace(total_blocks - position));
+        }
+
+        self.left_most_free_space = fs
+            .iter()
+            .position(|entry| matches!(entry, FSEntry::FreeSpace(_)))
+            .unwrap_or(fs.len());
+        self.fs = fs;
+    }
+
+    /// Moves every file, highest id first, to a free span on its left chosen by `strategy`.
+    /// Returns the number of files moved.
+    fn move_whole_files(&mut self, strategy: Strategy) -> usize {
+        let (mut files, free_spans, total_blocks) = self.blocks();
+        let mut free_spans = FreeSpans::new(free_spans);
+        let mut next_fit_from = 0;
+        let mut moved = 0;
+
+        files.sort_unstable_by_key(|&(_, file)| Reverse(file.file_id));
+        for (start, file) in files.iter_mut() {
+            let span = match strategy {
+                Strategy::FirstFit => free_spans
+                    .candidates(file.length, *start)
+                    .into_iter()
+                    .min_by_key(|&(span_start, _)| span_start),
+                Strategy::BestFit => free_spans.candidates(file.length, *start).first().copied(),
+                Strategy::WorstFit => free_spans.candidates(file.length, *start).last().copied(),
+                Strategy::NextFit => free_spans.next_fit(file.length, *start, next_fit_from),
+                Strategy::Defragment => unreachable!("Defragmenting does not pick free spans"),
+            };
+            let Some(span) = span else {
                 continue;
             };
 
-            self.insert_left_most_whole(entry, &mut current_file);
-            current_file -= 1;
-            // println!(
-            // "{} (left most: {}, current: {})",
-            // self, self.left_most_free_space, current_file
-            // );
+            // the space freed by the file is right of every file left to move
+            free_spans.take(span, file.length);
+            *start = span.0;
+            next_fit_from = span.0 + file.length;
+            moved += 1;
+        }
+
+        self.rebuild(files, total_blocks);
+        moved
+    }
+
+    /// Packs all files to the left, keeping their order.
+    fn defragment(&mut self) -> usize {
+        let (mut files, _, total_blocks) = self.blocks();
+        let mut position = 0;
+        let mut moved = 0;
+        for (start, file) in files.iter_mut() {
+            if *start != position {
+                *start = position;
+                moved += 1;
+            }
+
+            position += file.length;
+        }
+
+        self.rebuild(files, total_blocks);
+        moved
+    }
+
+    pub fn compact(&mut self, strategy: Strategy) -> CompactionReport {
+        let files_moved = match strategy {
+            Strategy::Defragment => self.defragment(),
+            _ => self.move_whole_files(strategy),
+        };
+
+        let (_, free_spans, total_blocks) = self.blocks();
+        CompactionReport {
+            strategy,
+            checksum: self.checksum(),
+            files_moved,
+            fragmentation: free_spans
+                .iter()
+                .filter(|&&(start, length)| start + length < total_blocks)
+                .count(),
+            largest_free_span: free_spans
+                .iter()
+                .map(|&(_, length)| length)
+                .max()
+                .unwrap_or(0),
         }
     }
 
+    /// Compacts a copy of the disk with every strategy.
+    pub fn simulate(&self) -> Vec<CompactionReport> {
+        Strategy::ALL
+            .into_iter()
+            .map(|strategy| self.clone().compact(strategy))
+            .collect()
+    }
+
+    fn shift_into_free_space_whole(&mut self) {
+        self.move_whole_files(Strategy::FirstFit);
+    }
+
     fn shift_into_free_space(&mut self) {
         // println!("{}", self);
         while self.fs.len() > self.left_most_free_space {
@@ -71,49 +208,6 @@ impl FSMap {
         }
     }
 
-    fn insert_left_most_whole(&mut self, entry: FSEntryInner, entry_index: &mut usize) {
-        let first_fitting = self.fs[self.left_most_free_space..*entry_index]
-            .iter()
-            .enumerate()
-            .find_map(|(i, e)| {
-                if let FSEntry::FreeSpace(free_space) = e {
-                    (*free_space >= entry.length).then_some(i + self.left_most_free_space)
-                } else {
-                    None
-                }
-            });
-
-        let first_fitting = match first_fitting {
-            Some(i) => i,
-            None => return,
-        };
-
-        self.fs[*entry_index] = FSEntry::FreeSpace(entry.length);
-        let FSEntry::FreeSpace(ref mut free_space) = self.fs[first_fitting] else {
-            panic!(
-                "first_fitting ({first_fitting}) was not a free space ({:?})",
-                self.fs[first_fitting]
-            );
-        };
-
-        *free_space -= entry.length;
-
-        if *free_space == 0 {
-            self.fs[first_fitting] = FSEntry::File(entry);
-        } else {
-            self.fs.insert(first_fitting, FSEntry::File(entry));
-            *entry_index += 1;
-        }
-
-        self.left_most_free_space = self
-            .fs
-            .iter()
-            .enumerate()
-            .find(|(_, &entry)| matches!(entry, FSEntry::FreeSpace(_)))
-            .map(|(i, _)| i)
-            .unwrap_or(self.fs.len());
-    }
-
     fn insert_left_most(&mut self, mut entry: FSEntryInner) {
         while entry.length > 0 {
             if self.fs.len() <= self.left_most_free_space {
@@ -157,14 +251,25 @@ impl FSMap {
     }
 }
 
+const FILE_SYMBOLS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// One character per block: the file id for ids below 10, then letters and `#` once those run
+/// out.
 impl std::fmt::Display for FSMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for entry in self.fs.iter() {
-            match entry {
-                FSEntry::FreeSpace(length) => write!(f, "{}", ".".repeat(*length))?,
+            let (symbol, length) = match entry {
+                FSEntry::FreeSpace(length) => ('.', *length),
                 FSEntry::File(entry) => {
-                    write!(f, "{}", entry.file_id.to_string().repeat(entry.length))?
+                    let symbol = FILE_SYMBOLS
+                        .get(entry.file_id as usize)
+                        .map_or('#', |&symbol| symbol as char);
+                    (symbol, entry.length)
                 }
+            };
+
+            for _ in 0..length {
+                write!(f, "{symbol}")?;
             }
         }
 
@@ -172,55 +277,331 @@ impl std::fmt::Display for FSMap {
     }
 }
 
-fn parse(input: &[u8]) -> FSMap {
-    let fs = input
-        .iter()
-        .enumerate()
-        .map(|(index, &length)| {
-            if index % 2 == 0 {
+/// How whole files are moved into free space. Files are always moved highest id first and only
+/// to the left, except when defragmenting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Left most free span that fits.
+    FirstFit,
+    /// Smallest free span that fits, the left most one if there are several.
+    BestFit,
+    /// Largest free span, the left most one if there are several.
+    WorstFit,
+    /// First free span that fits after the last moved file, wrapping around to the start.
+    NextFit,
+    /// Packs every file to the left in order, leaving a single free span at the end.
+    Defragment,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [
+        Self::FirstFit,
+        Self::BestFit,
+        Self::WorstFit,
+        Self::NextFit,
+        Self::Defragment,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompactionReport {
+    pub strategy: Strategy,
+    pub checksum: u64,
+    pub files_moved: usize,
+    /// Free spans with a file after them.
+    pub fragmentation: usize,
+    pub largest_free_span: usize,
+}
+
+impl std::fmt::Display for CompactionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?}: checksum {}, {} files moved, {} free spans left between files, largest free span {}",
+            self.strategy,
+            self.checksum,
+            self.files_moved,
+            self.fragmentation,
+            self.largest_free_span
+        )
+    }
+}
+
+/// Free spans by first block, with one min-heap per length of the first blocks to quickly find
+/// the left most span of a length. Heap entries of spans taken since are skipped lazily.
+struct FreeSpans {
+    by_start: BTreeMap<usize, usize>,
+    by_length: Vec<BinaryHeap<Reverse<usize>>>,
+}
+
+impl FreeSpans {
+    fn new(spans: Vec<FreeSpan>) -> Self {
+        let max_length = spans.iter().map(|&(_, length)| length).max().unwrap_or(0);
+        let mut by_length = vec![BinaryHeap::new(); max_length + 1];
+        for &(start, length) in &spans {
+            by_length[length].push(Reverse(start));
+        }
+
+        Self {
+            by_start: spans.into_iter().collect(),
+            by_length,
+        }
+    }
+
+    fn left_most_of_length(&mut self, length: usize) -> Option<usize> {
+        let spans = &mut self.by_length[length];
+        while let Some(&Reverse(start)) = spans.peek() {
+            if self.by_start.get(&start) == Some(&length) {
+                return Some(start);
+            }
+
+            spans.pop();
+        }
+
+        None
+    }
+
+    /// The left most span of every length of at least `length` starting before `before`, by
+    /// increasing length.
+    fn candidates(&mut self, length: usize, before: usize) -> Vec<FreeSpan> {
+        (length..self.by_length.len())
+            .filter_map(|length| {
+                self.left_most_of_length(length)
+                    .filter(|&start| start < before)
+                    .map(|start| (start, length))
+            })
+            .collect()
+    }
+
+    fn next_fit(&self, length: usize, before: usize, from: usize) -> Option<FreeSpan> {
+        let fits = |(&start, &span_length): (&usize, &usize)| {
+            (span_length >= length).then_some((start, span_length))
+        };
+
+        self.by_start
+            .range(from.min(before)..before)
+            .find_map(fits)
+            .or_else(|| self.by_start.range(..from.min(before)).find_map(fits))
+    }
+
+    /// Uses the first `used` blocks of `span`.
+    fn take(&mut self, (start, length): FreeSpan, used: usize) {
+        self.by_start.remove(&start);
+        if length > used {
+            self.by_start.insert(start + used, length - used);
+            self.by_length[length - used].push(Reverse(start + used));
+        }
+    }
+}
+
+fn parse(input: &str) -> Result<FSMap, ParseError> {
+    let disk_map = input.trim();
+    let fs = disk_map
+        .char_indices()
+        .map(|(index, c)| {
+            let length = c
+                .to_digit(10)
+                .ok_or_else(|| ParseError::at(9, input, &disk_map[index..], "Not a digit"))?
+                as usize;
+            Ok(if index % 2 == 0 {
                 FSEntry::File(FSEntryInner {
                     file_id: (index / 2) as u64,
-                    length: (length - b'0') as usize,
+                    length,
                 })
             } else {
-                FSEntry::FreeSpace((length - b'0') as usize)
-            }
+                FSEntry::FreeSpace(length)
+            })
         })
-        .collect();
+        .collect::<Result<_, _>>()?;
 
-    FSMap {
+    Ok(FSMap {
         fs,
         left_most_free_space: 1,
-    }
+    })
 }
 
-#[aoc(day09, part1)]
-fn part1(input: &str) -> u64 {
-    let mut fs = parse(input.trim().as_bytes());
+fn compacted_checksum(fs: &FSMap) -> u64 {
+    let mut fs = fs.clone();
     fs.shift_into_free_space();
     fs.checksum()
 }
 
-#[aoc(day09, part2)]
-fn part2(input: &str) -> u64 {
-    let mut fs = parse(input.trim().as_bytes());
+fn defragmented_checksum(fs: &FSMap) -> u64 {
+    let mut fs = fs.clone();
     fs.shift_into_free_space_whole();
     fs.checksum()
 }
 
+#[aoc(day09, part1)]
+fn part1(input: &str) -> Result<u64, ParseError> {
+    Ok(compacted_checksum(&parse(input)?))
+}
+
+#[aoc(day09, part2)]
+fn part2(input: &str) -> Result<u64, ParseError> {
+    Ok(defragmented_checksum(&parse(input)?))
+}
+
+pub struct Day09;
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+
+    type Input<'i> = FSMap;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        compacted_checksum(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        defragmented_checksum(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Random;
 
     const EXAMPLE: &str = "2333133121414131402";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(EXAMPLE), 1928);
+        assert_eq!(part1(EXAMPLE), Ok(1928));
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(EXAMPLE), 2858);
+        assert_eq!(part2(EXAMPLE), Ok(2858));
+
+        let mut fs = parse(EXAMPLE).unwrap();
+        fs.shift_into_free_space_whole();
+        assert_eq!(fs.to_string(), "00992111777.44.333....5555.6666.....8888..");
+    }
+
+    #[test]
+    fn strategies() {
+        let fs = parse(EXAMPLE).unwrap();
+        let layout = |strategy| {
+            let mut fs = fs.clone();
+            let report = fs.compact(strategy);
+            (fs.to_string(), report)
+        };
+
+        let (first_fit, report) = layout(Strategy::FirstFit);
+        assert_eq!(first_fit, "00992111777.44.333....5555.6666.....8888..");
+        assert_eq!(
+            report,
+            CompactionReport {
+                strategy: Strategy::FirstFit,
+                checksum: 2858,
+                files_moved: 4,
+                fragmentation: 5,
+                largest_free_span: 5,
+            }
+        );
+
+        let (defragmented, report) = layout(Strategy::Defragment);
+        assert_eq!(defragmented, "0011123334455556666777888899..............");
+        assert_eq!((report.files_moved, report.fragmentation), (9, 0));
+        assert_eq!(report.largest_free_span, 14);
+
+        let reports = fs.simulate();
+        assert_eq!(reports.len(), Strategy::ALL.len());
+        assert_eq!(reports[0], layout(Strategy::FirstFit).1);
+        for (report, strategy) in reports.iter().zip(Strategy::ALL) {
+            assert_eq!(report.strategy, strategy);
+        }
+    }
+
+    #[test]
+    fn fit_strategies() {
+        let fs = parse("121311211").unwrap();
+        assert_eq!(fs.to_string(), "0..1...2.33.4");
+        let layout = |strategy| {
+            let mut fs = fs.clone();
+            fs.compact(strategy);
+            fs.to_string()
+        };
+
+        assert_eq!(layout(Strategy::FirstFit), "042133.......");
+        assert_eq!(layout(Strategy::BestFit), "03312...4....");
+        assert_eq!(layout(Strategy::WorstFit), "033142.......");
+        assert_eq!(layout(Strategy::NextFit), "041.332......");
+    }
+
+    /// Disk map of `digits` pseudo-random digits.
+    fn disk_map(digits: usize, seed: u64) -> String {
+        let mut random = Random::new(seed);
+        (0..digits)
+            .map(|_| char::from(b'0' + random.below(10) as u8))
+            .collect()
+    }
+
+    /// Block by block first fit of whole files, highest id first.
+    fn naive_whole_checksum(input: &str) -> u64 {
+        let mut blocks = Vec::new();
+        for (index, length) in input.bytes().map(|c| (c - b'0') as usize).enumerate() {
+            let block = (index % 2 == 0).then_some(index as u64 / 2);
+            blocks.extend(std::iter::repeat_n(block, length));
+        }
+
+        for file_id in (0..input.len().div_ceil(2) as u64).rev() {
+            let Some(start) = blocks.iter().position(|&block| block == Some(file_id)) else {
+                continue;
+            };
+            let length = blocks[start..]
+                .iter()
+                .take_while(|&&block| block == Some(file_id))
+                .count();
+            let span = (0..start).find(|&span| {
+                blocks[span..span + length]
+                    .iter()
+                    .all(|block| block.is_none())
+            });
+            if let Some(span) = span {
+                blocks.copy_within(start..start + length, span);
+                blocks[start..start + length].fill(None);
+            }
+        }
+
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(position, block)| position as u64 * block.unwrap_or(0))
+            .sum()
+    }
+
+    #[test]
+    fn whole_files_match_naive() {
+        for seed in 0..20 {
+            let input = disk_map(301, seed);
+            assert_eq!(part2(&input), Ok(naive_whole_checksum(&input)), "{input}");
+        }
+
+        // one block files with one block gaps: the last files fill the first gaps in order, as
+        // long as that moves them left
+        let interleaved = |files: u64| {
+            let moved = (0..files)
+                .take_while(|&m| 2 * m + 1 < 2 * (files - 1 - m))
+                .count() as u64;
+            let checksum = (0..files - moved).map(|id| 2 * id * id).sum::<u64>()
+                + (0..moved)
+                    .map(|m| (2 * m + 1) * (files - 1 - m))
+                    .sum::<u64>();
+            ("11".repeat(files as usize - 1) + "1", checksum)
+        };
+        for files in [1, 2, 3, 4, 5, 10, 11] {
+            let (input, checksum) = interleaved(files);
+            assert_eq!(naive_whole_checksum(&input), checksum, "{input}");
+        }
+        let (input, checksum) = interleaved(100_001);
+        assert_eq!(part2(&input), Ok(checksum));
     }
 }
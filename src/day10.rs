@@ -1,49 +1,10 @@
 use fnv::FnvHashSet;
-use itertools::Itertools;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(isize, isize);
-
-const DIRECT_NEIGHBORS: [Position; 4] = [
-    Position(1, 0),
-    Position(0, 1),
-    Position(-1, 0),
-    Position(0, -1),
-];
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
-
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
+use crate::{
+    grid::{Grid, Position},
+    parse::ParseError,
+    solution::Solution,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Height {
@@ -125,31 +86,17 @@ impl From<u8> for Height {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct HeightMap {
-    map: Vec<Vec<Height>>,
+pub struct HeightMap {
+    map: Grid<Height>,
 }
 
 impl HeightMap {
-    fn get(&self, index: Position) -> Option<&Height> {
-        if index.0 < 0 || index.1 < 0 {
-            return None;
-        }
-
-        self.map.get(index.0 as usize)?.get(index.1 as usize)
-    }
-
-    fn iter_positions(&self) -> impl Iterator<Item = Position> {
-        let row_len = self.map[0].len();
-        (0..self.map.len()).flat_map(move |row_idx| {
-            (0..row_len).map(move |col_idx| Position(row_idx as isize, col_idx as isize))
-        })
-    }
-
     fn count_part1_paths(&self) -> usize {
         let mut head_positions = FnvHashSet::default();
-        self.iter_positions()
+        self.map
+            .positions()
             .filter_map(|pos| {
-                if self[pos] == Height::Zero {
+                if self.map[pos] == Height::Zero {
                     self.fill_trailhead_positions(pos, &mut head_positions);
                     let result = head_positions.len();
                     head_positions.clear();
@@ -166,24 +113,23 @@ impl HeightMap {
         current: Position,
         head_positions: &mut FnvHashSet<Position>,
     ) {
-        if self[current] == Height::Nine {
+        if self.map[current] == Height::Nine {
             head_positions.insert(current);
             return;
         }
 
-        for neighbor in DIRECT_NEIGHBORS {
-            if let Some(&other) = self.get(current + neighbor) {
-                if self[current].is_uphill_by_one(other) {
-                    self.fill_trailhead_positions(current + neighbor, head_positions);
-                }
+        for neighbor in self.map.neighbours(current) {
+            if self.map[current].is_uphill_by_one(self.map[neighbor]) {
+                self.fill_trailhead_positions(neighbor, head_positions);
             }
         }
     }
 
     fn count_part2_paths(&self) -> usize {
-        self.iter_positions()
+        self.map
+            .positions()
             .filter_map(|pos| {
-                if self[pos] == Height::Zero {
+                if self.map[pos] == Height::Zero {
                     Some(self.count_part2_paths_impl(pos))
                 } else {
                     None
@@ -193,59 +139,29 @@ impl HeightMap {
     }
 
     fn count_part2_paths_impl(&self, current: Position) -> usize {
-        if self[current] == Height::Nine {
+        if self.map[current] == Height::Nine {
             return 1;
         }
 
-        DIRECT_NEIGHBORS
-            .into_iter()
-            .map(|neighbor| {
-                if let Some(&other) = self.get(current + neighbor) {
-                    if self[current].is_uphill_by_one(other) {
-                        return self.count_part2_paths_impl(current + neighbor);
-                    }
-                }
-
-                0
-            })
+        self.map
+            .neighbours(current)
+            .filter(|&neighbor| self.map[current].is_uphill_by_one(self.map[neighbor]))
+            .map(|neighbor| self.count_part2_paths_impl(neighbor))
             .sum()
     }
 }
 
 impl std::fmt::Display for HeightMap {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in self.map.iter() {
-            for height in row.iter() {
-                write!(f, "{}", height)?;
-            }
-
-            writeln!(f)?;
-        }
-
-        Ok(())
-    }
-}
-
-impl std::ops::Index<Position> for HeightMap {
-    type Output = Height;
-
-    fn index(&self, index: Position) -> &Self::Output {
-        self.get(index)
-            .unwrap_or_else(|| panic!("Could not index position {index} in heightmap"))
+        write!(f, "{}", self.map)
     }
 }
 
 #[aoc_generator(day10)]
-fn parse(input: &str) -> HeightMap {
-    HeightMap {
-        map: input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(str::trim)
-            .map(str::as_bytes)
-            .map(|line| line.iter().copied().map_into().collect())
-            .collect(),
-    }
+fn parse(input: &str) -> Result<HeightMap, ParseError> {
+    Ok(HeightMap {
+        map: Grid::parse(input).map_err(|error| ParseError::from_grid(10, error))?,
+    })
 }
 
 #[aoc(day10, part1)]
@@ -258,6 +174,28 @@ fn part2(height_map: &HeightMap) -> usize {
     height_map.count_part2_paths()
 }
 
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input<'i> = HeightMap;
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -316,7 +254,7 @@ mod tests {
     #[test]
     fn part1_example() {
         for (example, expected) in PART1_EXAMPLES {
-            assert_eq!(part1(&parse(example)), expected);
+            assert_eq!(part1(&parse(example).unwrap()), expected);
         }
     }
 
@@ -366,7 +304,7 @@ mod tests {
     #[test]
     fn part2_example() {
         for (example, expected) in PART2_EXAMPLES {
-            assert_eq!(part2(&parse(example)), expected);
+            assert_eq!(part2(&parse(example).unwrap()), expected);
         }
     }
 }
@@ -1,8 +1,12 @@
+use std::error::Error;
+
 use fnv::FnvHashMap;
 use itertools::Itertools;
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Stone(u64);
+pub struct Stone(u64);
 
 impl std::fmt::Debug for Stone {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -12,16 +16,27 @@ impl std::fmt::Debug for Stone {
 
 impl Stone {
     fn next_state(self) -> ModifyStoneResult {
+        self.try_next_state()
+            .expect("Stone engraving does not fit in a u64")
+    }
+
+    /// `None` if the engraving of the new stone does not fit in a u64.
+    fn try_next_state(self) -> Option<ModifyStoneResult> {
         if self.0 == 0 {
-            return ModifyStoneResult::ValueChanged(Self(1));
+            return Some(ModifyStoneResult::ValueChanged(Self(1)));
         }
 
-        let digit_count = (self.0 as f64).log10().floor() as u32 + 1;
-        if digit_count % 2 == 0 {
+        let digit_count = self.0.ilog10() + 1;
+        if digit_count.is_multiple_of(2) {
             let pow = 10_u64.pow(digit_count / 2);
-            ModifyStoneResult::StoneSplit(Self(self.0 / pow), Self(self.0 % pow))
+            Some(ModifyStoneResult::StoneSplit(
+                Self(self.0 / pow),
+                Self(self.0 % pow),
+            ))
         } else {
-            ModifyStoneResult::ValueChanged(Self(self.0 * 2024))
+            self.0
+                .checked_mul(2024)
+                .map(|value| ModifyStoneResult::ValueChanged(Self(value)))
         }
     }
 }
@@ -43,12 +58,22 @@ fn method1_do_cycle(stones: impl IntoIterator<Item = Stone>, result: &mut Vec<St
     }
 }
 
-#[allow(unused)]
-fn method1_cycle_n_times(input: &str, iterations: usize) -> u64 {
-    let mut stones: Vec<Stone> = input
+#[aoc_generator(day11)]
+fn parse(input: &str) -> Result<Vec<Stone>, ParseError> {
+    input
         .split_whitespace()
-        .map(|stone| Stone(stone.parse().expect("Could not parse u64")))
-        .collect();
+        .map(|stone| {
+            stone
+                .parse()
+                .map(Stone)
+                .map_err(|error| ParseError::at(11, input, stone, error))
+        })
+        .collect()
+}
+
+#[allow(unused)]
+fn method1_cycle_n_times(stones: &[Stone], iterations: usize) -> u64 {
+    let mut stones = stones.to_vec();
 
     // println!("Stones: {:?}", &stones);
     let mut result = vec![];
@@ -102,12 +127,7 @@ fn method2_recurse(
     }
 }
 
-fn method2_cycle_n_times(input: &str, iterations: usize) -> u64 {
-    let stones = input
-        .split_whitespace()
-        .map(|stone| Stone(stone.parse().expect("Could not parse u64")))
-        .collect_vec();
-
+fn method2_cycle_n_times(stones: &[Stone], iterations: usize) -> u64 {
     if iterations == 0 {
         return stones.len() as u64;
     }
@@ -115,19 +135,331 @@ fn method2_cycle_n_times(input: &str, iterations: usize) -> u64 {
     let mut cache = FnvHashMap::default();
 
     stones
-        .into_iter()
-        .map(|stone| method2_recurse(stone, iterations, &mut cache))
+        .iter()
+        .map(|&stone| method2_recurse(stone, iterations, &mut cache))
         .sum()
 }
 
+/// The rules of the puzzle, see [`Rules::parse`].
+pub const DEFAULT_RULES: &str = "value == 0 => replace 1
+digits % 2 == 0 => split 2
+any => multiply 2024";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Predicate {
+    Any,
+    ValueEquals(u64),
+    DigitsModulo { modulo: u32, remainder: u32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Action {
+    Replace(u64),
+    /// Splits the digits into that many parts of equal length, the first part also gets the
+    /// digits left over.
+    Split(u32),
+    Multiply(u64),
+    Add(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Rule {
+    predicate: Predicate,
+    action: Action,
+}
+
+/// Ordered stone rules, the first rule whose predicate matches is applied. Stones matching no
+/// rule stay as they are.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rules {
+    base: u64,
+    rules: Vec<Rule>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self::parse(DEFAULT_RULES).expect("Default rules are valid")
+    }
+}
+
+/// More parts than a u64 has digits in base 2 would always be empty.
+const MAX_SPLIT_PARTS: u32 = u64::BITS;
+
+fn parse_number<T: std::str::FromStr>(spec: &str, token: &str) -> Result<T, ParseError>
+where
+    T::Err: std::fmt::Display,
+{
+    token
+        .parse()
+        .map_err(|error| ParseError::at(11, spec, token, error))
+}
+
+impl Rules {
+    /// Parses one rule per line, `predicate => action`, with an optional `base <k>` line for the
+    /// digit rules (10 by default). Predicates are `any`, `value == <n>` and
+    /// `digits % <m> == <r>`, actions are `replace <n>`, `split <parts>`, `multiply <n>` and
+    /// `add <n>`. Everything after a `#` is ignored.
+    pub fn parse(spec: &str) -> Result<Self, ParseError> {
+        let mut base = 10;
+        let mut rules = Vec::new();
+        for line in spec.lines() {
+            let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(k) = line.strip_prefix("base ") {
+                let k = k.trim();
+                base = parse_number(spec, k)?;
+                if base < 2 {
+                    return Err(ParseError::at(11, spec, k, "Base must be at least 2"));
+                }
+
+                continue;
+            }
+
+            let (predicate, action) = line.split_once("=>").ok_or_else(|| {
+                ParseError::at(11, spec, line, "Expected \"predicate => action\"")
+            })?;
+            rules.push(Rule {
+                predicate: Self::parse_predicate(spec, predicate)?,
+                action: Self::parse_action(spec, action)?,
+            });
+        }
+
+        Ok(Self { base, rules })
+    }
+
+    fn parse_predicate(spec: &str, predicate: &str) -> Result<Predicate, ParseError> {
+        match predicate.split_whitespace().collect_vec()[..] {
+            ["any"] => Ok(Predicate::Any),
+            ["value", "==", value] => Ok(Predicate::ValueEquals(parse_number(spec, value)?)),
+            ["digits", "%", modulo, "==", remainder] => {
+                let (modulo, remainder) =
+                    (parse_number(spec, modulo)?, parse_number(spec, remainder)?);
+                if remainder >= modulo {
+                    return Err(ParseError::at(
+                        11,
+                        spec,
+                        predicate,
+                        "Remainder is never reached",
+                    ));
+                }
+
+                Ok(Predicate::DigitsModulo { modulo, remainder })
+            }
+            _ => Err(ParseError::at(11, spec, predicate, "Unknown predicate")),
+        }
+    }
+
+    fn parse_action(spec: &str, action: &str) -> Result<Action, ParseError> {
+        let tokens = action.split_whitespace().collect_vec();
+        let [name, value] = tokens[..] else {
+            return Err(ParseError::at(
+                11,
+                spec,
+                action,
+                "Expected \"action <number>\"",
+            ));
+        };
+
+        match name {
+            "replace" => Ok(Action::Replace(parse_number(spec, value)?)),
+            "split" => match parse_number(spec, value)? {
+                0 => Err(ParseError::at(11, spec, value, "Cannot split into 0 parts")),
+                parts if parts > MAX_SPLIT_PARTS => Err(ParseError::at(
+                    11,
+                    spec,
+                    value,
+                    format!("Cannot split into more than {MAX_SPLIT_PARTS} parts"),
+                )),
+                parts => Ok(Action::Split(parts)),
+            },
+            "multiply" => Ok(Action::Multiply(parse_number(spec, value)?)),
+            "add" => Ok(Action::Add(parse_number(spec, value)?)),
+            _ => Err(ParseError::at(11, spec, name, "Unknown action")),
+        }
+    }
+
+    fn digit_count(&self, value: u64) -> u32 {
+        if value == 0 {
+            1
+        } else {
+            value.ilog(self.base) + 1
+        }
+    }
+
+    /// The stones replacing `stone`, `None` if one of them does not fit in a u64.
+    fn apply(&self, stone: Stone) -> Option<Vec<Stone>> {
+        let Some(rule) = self.rules.iter().find(|rule| match rule.predicate {
+            Predicate::Any => true,
+            Predicate::ValueEquals(value) => stone.0 == value,
+            Predicate::DigitsModulo { modulo, remainder } => {
+                self.digit_count(stone.0) % modulo == remainder
+            }
+        }) else {
+            return Some(vec![stone]);
+        };
+
+        let stones = match rule.action {
+            Action::Replace(value) => vec![Stone(value)],
+            // too few digits for every part to get one
+            Action::Split(parts) if self.digit_count(stone.0) < parts => vec![stone],
+            Action::Split(parts) => {
+                let part_size = self.base.checked_pow(self.digit_count(stone.0) / parts)?;
+                let mut value = stone.0;
+                let mut stones = vec![Stone(0); parts as usize];
+                for part in stones.iter_mut().skip(1).rev() {
+                    *part = Stone(value % part_size);
+                    value /= part_size;
+                }
+                stones[0] = Stone(value);
+
+                stones
+            }
+            Action::Multiply(factor) => vec![Stone(stone.0.checked_mul(factor)?)],
+            Action::Add(term) => vec![Stone(stone.0.checked_add(term)?)],
+        };
+
+        Some(stones)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CountError {
+    StoneTooLarge { stone: u64, blinks: usize },
+    CountOverflow { blinks: usize },
+}
+
+impl std::fmt::Display for CountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StoneTooLarge { stone, blinks } => write!(
+                f,
+                "Stone {stone} grows past a u64 during blink {}",
+                blinks + 1
+            ),
+            Self::CountOverflow { blinks } => write!(
+                f,
+                "Number of stones does not fit in a u128 after {} blinks",
+                blinks + 1
+            ),
+        }
+    }
+}
+
+impl Error for CountError {}
+
+/// Number of stones per engraving: the order of the stones never matters, and after a few
+/// blinks there are only a few thousand distinct engravings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoneCounter {
+    stones: FnvHashMap<Stone, u128>,
+    blinks: usize,
+    rules: Rules,
+}
+
+impl StoneCounter {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::with_rules(input, Rules::default())
+    }
+
+    pub fn with_rules(input: &str, rules: Rules) -> Result<Self, ParseError> {
+        let mut stones = FnvHashMap::default();
+        for stone in parse(input)? {
+            *stones.entry(stone).or_default() += 1;
+        }
+
+        Ok(Self {
+            stones,
+            blinks: 0,
+            rules,
+        })
+    }
+
+    pub fn blinks(&self) -> usize {
+        self.blinks
+    }
+
+    /// Blinks once. On error the counter is left as it was.
+    pub fn blink(&mut self) -> Result<(), CountError> {
+        let mut next = FnvHashMap::with_capacity_and_hasher(self.stones.len(), Default::default());
+        let mut add = |stone: Stone, count: u128| {
+            let entry = next.entry(stone).or_default();
+            *entry = u128::checked_add(*entry, count).ok_or(CountError::CountOverflow {
+                blinks: self.blinks,
+            })?;
+            Ok(())
+        };
+
+        for (&stone, &count) in &self.stones {
+            let new_stones = self.rules.apply(stone).ok_or(CountError::StoneTooLarge {
+                stone: stone.0,
+                blinks: self.blinks,
+            })?;
+            for new_stone in new_stones {
+                add(new_stone, count)?;
+            }
+        }
+
+        self.stones = next;
+        self.blinks += 1;
+        Ok(())
+    }
+
+    pub fn blink_times(&mut self, times: usize) -> Result<(), CountError> {
+        (0..times).try_for_each(|_| self.blink())
+    }
+
+    pub fn total(&self) -> Result<u128, CountError> {
+        self.stones
+            .values()
+            .try_fold(0_u128, |total, &count| total.checked_add(count))
+            .ok_or(CountError::CountOverflow {
+                blinks: self.blinks.saturating_sub(1),
+            })
+    }
+
+    /// Number of stones per engraving, sorted by engraving.
+    pub fn distribution(&self) -> Vec<(u64, u128)> {
+        self.stones
+            .iter()
+            .map(|(stone, &count)| (stone.0, count))
+            .sorted_unstable()
+            .collect()
+    }
+}
+
 #[aoc(day11, part1)]
-fn part1(input: &str) -> u64 {
-    method2_cycle_n_times(input, 25)
+fn part1(stones: &[Stone]) -> u64 {
+    method2_cycle_n_times(stones, 25)
 }
 
 #[aoc(day11, part2)]
-fn part2(input: &str) -> u64 {
-    method2_cycle_n_times(input, 75)
+fn part2(stones: &[Stone]) -> u64 {
+    method2_cycle_n_times(stones, 75)
+}
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Input<'i> = Vec<Stone>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -139,15 +471,161 @@ mod tests {
 
     #[test]
     fn method1_examples() {
-        assert_eq!(method1_cycle_n_times(EXAMPLE1, 1), 7);
-        assert_eq!(method1_cycle_n_times(EXAMPLE2, 6), 22);
-        assert_eq!(method1_cycle_n_times(EXAMPLE2, 25), 55312);
+        let (example1, example2) = (parse(EXAMPLE1).unwrap(), parse(EXAMPLE2).unwrap());
+        assert_eq!(method1_cycle_n_times(&example1, 1), 7);
+        assert_eq!(method1_cycle_n_times(&example2, 6), 22);
+        assert_eq!(method1_cycle_n_times(&example2, 25), 55312);
     }
 
     #[test]
     fn method2_examples() {
-        assert_eq!(method2_cycle_n_times(EXAMPLE1, 1), 7);
-        assert_eq!(method2_cycle_n_times(EXAMPLE2, 6), 22);
-        assert_eq!(method2_cycle_n_times(EXAMPLE2, 25), 55312);
+        let (example1, example2) = (parse(EXAMPLE1).unwrap(), parse(EXAMPLE2).unwrap());
+        assert_eq!(method2_cycle_n_times(&example1, 1), 7);
+        assert_eq!(method2_cycle_n_times(&example2, 6), 22);
+        assert_eq!(method2_cycle_n_times(&example2, 25), 55312);
+        assert_eq!(part1(&example2), 55312);
+
+        assert_eq!(
+            parse("125 17\n1x").unwrap_err().to_string(),
+            "Day 11, line 2, column 1: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn stone_counter() {
+        let mut counter = StoneCounter::parse(EXAMPLE2).unwrap();
+        counter.blink_times(6).unwrap();
+        assert_eq!(counter.blinks(), 6);
+        assert_eq!(counter.total(), Ok(22));
+        assert_eq!(
+            counter.distribution(),
+            [
+                (0, 2),
+                (2, 4),
+                (3, 1),
+                (4, 1),
+                (6, 2),
+                (7, 1),
+                (8, 1),
+                (40, 2),
+                (48, 2),
+                (80, 1),
+                (96, 1),
+                (2024, 1),
+                (4048, 1),
+                (14168, 1),
+                (2097446912, 1)
+            ]
+        );
+
+        for input in [EXAMPLE1, EXAMPLE2, "0", "7 7 7 1234567"] {
+            let mut counter = StoneCounter::parse(input).unwrap();
+            let stones = parse(input).unwrap();
+            for blinks in 1..=40 {
+                counter.blink().unwrap();
+                if blinks <= 25 {
+                    let expected = method1_cycle_n_times(&stones, blinks);
+                    assert_eq!(counter.total(), Ok(expected as u128), "{input}, {blinks}");
+                }
+                let expected = method2_cycle_n_times(&stones, blinks);
+                assert_eq!(counter.total(), Ok(expected as u128), "{input}, {blinks}");
+            }
+        }
+    }
+
+    #[test]
+    fn default_rules() {
+        let rules = Rules::default();
+        for value in (0..10_000).chain([253000, 1036288, 99999999, 123456789, 9999999999]) {
+            let expected = match Stone(value).next_state() {
+                ModifyStoneResult::ValueChanged(stone) => vec![stone],
+                ModifyStoneResult::StoneSplit(stone1, stone2) => vec![stone1, stone2],
+            };
+            assert_eq!(rules.apply(Stone(value)), Some(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn custom_rules() {
+        let rules = Rules::parse(
+            "# split into three in base 2
+            base 2
+            value == 1 => add 6 # 0b111
+            digits % 3 == 0 => split 3
+            any => multiply 3",
+        )
+        .unwrap();
+        let apply = |value| {
+            rules
+                .apply(Stone(value))
+                .map(|stones| stones.iter().map(|stone| stone.0).collect_vec())
+        };
+        assert_eq!(apply(1), Some(vec![7]));
+        assert_eq!(apply(0b110101), Some(vec![0b11, 0b01, 0b01]));
+        assert_eq!(apply(0b10), Some(vec![6]));
+        assert_eq!(apply(u64::MAX), None);
+
+        let mut counter = StoneCounter::with_rules("1", rules).unwrap();
+        counter.blink_times(2).unwrap();
+        assert_eq!(counter.distribution(), [(1, 3)]);
+
+        let rules = Rules::parse("digits % 2 == 1 => split 2").unwrap();
+        assert_eq!(rules.apply(Stone(12345)), Some(vec![Stone(123), Stone(45)]));
+        assert_eq!(rules.apply(Stone(1234)), Some(vec![Stone(1234)]));
+        let rules = Rules::parse("any => split 3").unwrap();
+        assert_eq!(rules.apply(Stone(12)), Some(vec![Stone(12)]));
+        assert_eq!(
+            rules.apply(Stone(1203)),
+            Some(vec![Stone(12), Stone(0), Stone(3)])
+        );
+
+        for spec in [
+            "any",
+            "values == 1 => add 1",
+            "any => split 0",
+            "digits % 2 == 2 => add 1",
+            "base 1",
+            "any => double",
+        ] {
+            assert!(Rules::parse(spec).is_err(), "{spec}");
+        }
+        assert_eq!(
+            Rules::parse(
+                "any => add 1
+value == x => add 1"
+            )
+            .unwrap_err()
+            .to_string(),
+            "Day 11, line 2, column 10: invalid digit found in string"
+        );
+        assert_eq!(
+            Rules::parse("any => split 4000000000")
+                .unwrap_err()
+                .to_string(),
+            "Day 11, line 1, column 14: Cannot split into more than 64 parts"
+        );
+    }
+
+    #[test]
+    fn stone_counter_overflow() {
+        let mut counter = StoneCounter::parse(EXAMPLE2).unwrap();
+        // the number of stones grows about 1.5 times per blink
+        counter.blink_times(200).unwrap();
+        assert!(counter.total().is_ok());
+        assert_eq!(
+            counter.blink_times(300),
+            Err(CountError::CountOverflow { blinks: 218 })
+        );
+        assert_eq!(counter.blinks(), 218);
+
+        let mut counter = StoneCounter::parse("9999999999999999999").unwrap();
+        assert_eq!(
+            counter.blink(),
+            Err(CountError::StoneTooLarge {
+                stone: 9999999999999999999,
+                blinks: 0
+            })
+        );
+        assert!(StoneCounter::parse("12 x").is_err());
     }
 }
@@ -1,5 +1,11 @@
 use std::fmt::Write;
 
+use crate::{
+    grid::{Grid, Position},
+    parse::ParseError,
+    solution::Solution,
+};
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 struct Plot(u8);
 
@@ -21,16 +27,6 @@ impl std::fmt::Display for Plot {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(isize, isize);
-
-const DIRECT_NEIGHBORS: [Position; 4] = [
-    Position(1, 0),
-    Position(0, 1),
-    Position(-1, 0),
-    Position(0, -1),
-];
-
 const DIRECT_NEIGHBORS_DIR: [(Position, Direction); 4] = [
     (Position(1, 0), Direction::Down),
     (Position(0, 1), Direction::Right),
@@ -38,127 +34,36 @@ const DIRECT_NEIGHBORS_DIR: [(Position, Direction); 4] = [
     (Position(0, -1), Direction::Left),
 ];
 
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
-
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
-
-#[derive(Debug, Clone)]
-struct Garden {
-    plots: Vec<Vec<Plot>>,
-}
-
 #[derive(Debug, Clone)]
-struct VisitedList {
-    visited: Vec<Vec<bool>>,
-}
-
-impl VisitedList {
-    fn get(&self, index: Position) -> Option<&bool> {
-        if index.0 < 0 || index.1 < 0 {
-            return None;
-        }
-
-        self.visited.get(index.0 as usize)?.get(index.1 as usize)
-    }
-
-    fn get_mut(&mut self, index: Position) -> Option<&mut bool> {
-        if index.0 < 0 || index.1 < 0 {
-            return None;
-        }
-
-        self.visited
-            .get_mut(index.0 as usize)?
-            .get_mut(index.1 as usize)
-    }
+pub struct Garden {
+    plots: Grid<Plot>,
 }
 
-impl std::ops::Index<Position> for VisitedList {
-    type Output = bool;
-
-    fn index(&self, index: Position) -> &Self::Output {
-        self.get(index)
-            .unwrap_or_else(|| panic!("Could not index position {index} in visited list"))
-    }
-}
-
-impl std::ops::IndexMut<Position> for VisitedList {
-    fn index_mut(&mut self, index: Position) -> &mut Self::Output {
-        self.get_mut(index)
-            .unwrap_or_else(|| panic!("Could not index position {index} in visited list"))
-    }
-}
+type VisitedList = Grid<bool>;
 
 impl Garden {
-    fn get(&self, index: Position) -> Option<&Plot> {
-        if index.0 < 0 || index.1 < 0 {
-            return None;
-        }
-
-        self.plots.get(index.0 as usize)?.get(index.1 as usize)
-    }
-
-    fn iter_positions(&self) -> impl Iterator<Item = Position> {
-        let row_len = self.plots[0].len();
-        (0..self.plots.len()).flat_map(move |row_idx| {
-            (0..row_len).map(move |col_idx| Position(row_idx as isize, col_idx as isize))
-        })
-    }
-
     fn init_visited_list(&self) -> VisitedList {
-        let row_length = self.plots[0].len();
-
-        VisitedList {
-            visited: vec![vec![false; row_length]; self.plots.len()],
-        }
+        Grid::from_elem(self.plots.height(), self.plots.width(), false)
     }
 
     fn part1_do_region(&self, position: Position, visited: &mut VisitedList) -> Region {
         let mut region = Region {
-            region_plot: self[position],
+            region_plot: self.plots[position],
             area: 1,
             perimeter: 0,
         };
 
         let mut to_process = vec![position];
         while let Some(position) = to_process.pop() {
-            for neighbor in DIRECT_NEIGHBORS.into_iter().map(|delta| position + delta) {
+            for neighbor in position.direct_neighbors() {
                 if visited.get(neighbor).is_some_and(|visited| !visited)
-                    && self[neighbor] == region.region_plot
+                    && self.plots[neighbor] == region.region_plot
                 {
                     visited[neighbor] = true;
                     region.area += 1;
                     to_process.push(neighbor);
                 } else if !self
+                    .plots
                     .get(neighbor)
                     .is_some_and(|&plot| plot == region.region_plot)
                 {
@@ -172,7 +77,8 @@ impl Garden {
 
     fn part1(&self) -> u64 {
         let mut visited = self.init_visited_list();
-        self.iter_positions()
+        self.plots
+            .positions()
             .filter_map(|position| {
                 if visited[position] {
                     None
@@ -188,7 +94,7 @@ impl Garden {
 
     fn part2_do_region(&self, position: Position, visited: &mut VisitedList) -> Region {
         let mut region = Region {
-            region_plot: self[position],
+            region_plot: self.plots[position],
             area: 1,
             perimeter: 0,
         };
@@ -201,12 +107,13 @@ impl Garden {
                 .map(|(delta, direction)| (position + delta, direction))
             {
                 if visited.get(neighbor).is_some_and(|visited| !visited)
-                    && self[neighbor] == region.region_plot
+                    && self.plots[neighbor] == region.region_plot
                 {
                     visited[neighbor] = true;
                     region.area += 1;
                     to_process.push(neighbor);
                 } else if !self
+                    .plots
                     .get(neighbor)
                     .is_some_and(|&plot| plot == region.region_plot)
                 {
@@ -234,7 +141,8 @@ impl Garden {
 
     fn part2(&self) -> u64 {
         let mut visited = self.init_visited_list();
-        self.iter_positions()
+        self.plots
+            .positions()
             .filter_map(|position| {
                 if visited[position] {
                     None
@@ -249,15 +157,6 @@ impl Garden {
     }
 }
 
-impl std::ops::Index<Position> for Garden {
-    type Output = Plot;
-
-    fn index(&self, index: Position) -> &Self::Output {
-        self.get(index)
-            .unwrap_or_else(|| panic!("Could not index position {index} in garden"))
-    }
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Region {
     region_plot: Plot,
@@ -352,20 +251,10 @@ impl Side {
 }
 
 #[aoc_generator(day12)]
-fn parse(input: &str) -> Garden {
-    Garden {
-        plots: input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                line.trim()
-                    .as_bytes()
-                    .iter()
-                    .map(|b| Plot::from(*b))
-                    .collect()
-            })
-            .collect(),
-    }
+fn parse(input: &str) -> Result<Garden, ParseError> {
+    Ok(Garden {
+        plots: Grid::parse(input).map_err(|error| ParseError::from_grid(12, error))?,
+    })
 }
 
 #[aoc(day12, part1)]
@@ -378,6 +267,28 @@ fn part2(input: &Garden) -> u64 {
     input.part2()
 }
 
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+
+    type Input<'i> = Garden;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -419,33 +330,33 @@ AAAAAA";
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse(EXAMPLE1)), 140);
-        assert_eq!(part1(&parse(EXAMPLE2)), 772);
-        assert_eq!(part1(&parse(EXAMPLE3)), 1930);
+        assert_eq!(part1(&parse(EXAMPLE1).unwrap()), 140);
+        assert_eq!(part1(&parse(EXAMPLE2).unwrap()), 772);
+        assert_eq!(part1(&parse(EXAMPLE3).unwrap()), 1930);
     }
 
     #[test]
     fn part2_example1() {
-        assert_eq!(part2(&parse(EXAMPLE1)), 80);
+        assert_eq!(part2(&parse(EXAMPLE1).unwrap()), 80);
     }
 
     #[test]
     fn part2_example2() {
-        assert_eq!(part2(&parse(EXAMPLE2)), 436);
+        assert_eq!(part2(&parse(EXAMPLE2).unwrap()), 436);
     }
 
     #[test]
     fn part2_example3() {
-        assert_eq!(part2(&parse(EXAMPLE3)), 1206);
+        assert_eq!(part2(&parse(EXAMPLE3).unwrap()), 1206);
     }
 
     #[test]
     fn part2_example4() {
-        assert_eq!(part2(&parse(EXAMPLE4)), 236);
+        assert_eq!(part2(&parse(EXAMPLE4).unwrap()), 236);
     }
 
     #[test]
     fn part2_example5() {
-        assert_eq!(part2(&parse(EXAMPLE5)), 368);
+        assert_eq!(part2(&parse(EXAMPLE5).unwrap()), 368);
     }
 }
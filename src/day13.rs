@@ -1,127 +1,230 @@
-use approx::abs_diff_eq;
 use itertools::Itertools;
+#[cfg(feature = "linalg")]
 use ndarray::prelude::*;
+#[cfg(feature = "linalg")]
 use ndarray_linalg::Solve;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(isize, isize);
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
+use crate::{grid::Position, parse::ParseError, solution::Solution};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct ClawMachine {
+pub struct ClawMachine {
     button_a: Position,
     button_b: Position,
     target: Position,
 }
 
-fn parse_button(button: char, s: &str) -> Position {
-    let (x, y) = s
+fn parse_int(input: &str, num: &str) -> Result<isize, ParseError> {
+    num.parse()
+        .map_err(|error| ParseError::at(13, input, num, error))
+}
+
+fn parse_button(input: &str, button: char, s: &str) -> Result<Position, ParseError> {
+    let coordinates = s
         .trim()
         .strip_prefix("Button ")
-        .expect("Failed to strip \"Button \"")
-        .strip_prefix(button)
-        .expect("Failed to parse button A/B")
-        .strip_prefix(": X+")
-        .expect("Failed to strip \": X+\"")
+        .and_then(|s| s.strip_prefix(button))
+        .and_then(|s| s.strip_prefix(": X+"))
+        .ok_or_else(|| ParseError::at(13, input, s, format!("Expected \"Button {button}: X+\"")))?;
+    let (x, y) = coordinates
         .split_once(", Y+")
-        .expect("Failed to strip \", Y+\"");
+        .ok_or_else(|| ParseError::at(13, input, coordinates, "Could not split on \", Y+\""))?;
 
-    Position(
-        x.parse().expect("Failed to parse int"),
-        y.parse().expect("Failed to parse int"),
-    )
+    Ok(Position(parse_int(input, x)?, parse_int(input, y)?))
 }
 
-fn parse_target(s: &str) -> Position {
-    let (x, y) = s
+fn parse_target(input: &str, s: &str) -> Result<Position, ParseError> {
+    let coordinates = s
         .trim()
         .strip_prefix("Prize: X=")
-        .expect("Could not strip \"Prize: X=\"")
+        .ok_or_else(|| ParseError::at(13, input, s, "Expected \"Prize: X=\""))?;
+    let (x, y) = coordinates
         .split_once(", Y=")
-        .expect("Could not split on \", Y=\"");
+        .ok_or_else(|| ParseError::at(13, input, coordinates, "Could not split on \", Y=\""))?;
 
-    Position(
-        x.parse().expect("Failed to parse int"),
-        y.parse().expect("Failed to parse int"),
-    )
+    Ok(Position(parse_int(input, x)?, parse_int(input, y)?))
 }
 
 #[aoc_generator(day13)]
-fn parse(input: &str) -> Vec<ClawMachine> {
+fn parse(input: &str) -> Result<Vec<ClawMachine>, ParseError> {
     input
         .lines()
         .filter(|line| !line.is_empty())
         .tuples()
-        .map(|(button_a, button_b, target)| ClawMachine {
-            button_a: parse_button('A', button_a),
-            button_b: parse_button('B', button_b),
-            target: parse_target(target),
+        .map(|(button_a, button_b, target)| {
+            Ok(ClawMachine {
+                button_a: parse_button(input, 'A', button_a)?,
+                button_b: parse_button(input, 'B', button_b)?,
+                target: parse_target(input, target)?,
+            })
         })
-        .collect_vec()
+        .try_collect()
 }
 
-fn get_integers_out(a: f64, b: f64) -> Option<(u64, u64)> {
-    let a_closest = a.round();
-    let b_closest = b.round();
-    if a_closest < 0. || b_closest < 0. {
-        return None;
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        return (a.abs(), a.signum(), 0);
+    }
+
+    let (gcd, x, y) = extended_gcd(b, a.rem_euclid(b));
+    (gcd, y, x - a.div_euclid(b) * y)
+}
+
+fn div_floor(a: i128, b: i128) -> i128 {
+    let quotient = a / b;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        quotient - 1
+    } else {
+        quotient
+    }
+}
+
+fn div_ceil(a: i128, b: i128) -> i128 {
+    -div_floor(-a, b)
+}
+
+/// Non-negative `(a, b)` with `a * p + b * q == target` minimising `3 * a + b`.
+fn cheapest_on_line(p: i128, q: i128, target: i128) -> Option<(i128, i128)> {
+    if p == 0 && q == 0 {
+        return (target == 0).then_some((0, 0));
     }
 
-    if !abs_diff_eq!(a, a_closest, epsilon = 0.001) {
+    let (gcd, x, y) = extended_gcd(p, q);
+    if target % gcd != 0 {
         return None;
     }
 
-    if !abs_diff_eq!(b, b_closest, epsilon = 0.001) {
+    // every solution is (a0 + k * step_a, b0 + k * step_b)
+    let (a0, b0) = (x * (target / gcd), y * (target / gcd));
+    let (step_a, step_b) = (q / gcd, -p / gcd);
+
+    let mut lower: Option<i128> = None;
+    let mut upper: Option<i128> = None;
+    for (start, step) in [(a0, step_a), (b0, step_b)] {
+        match step.signum() {
+            1 => lower = Some(lower.unwrap_or(i128::MIN).max(div_ceil(-start, step))),
+            -1 => upper = Some(upper.unwrap_or(i128::MAX).min(div_floor(start, -step))),
+            _ if start < 0 => return None,
+            _ => (),
+        }
+    }
+
+    if upper.zip(lower).is_some_and(|(upper, lower)| lower > upper) {
         return None;
     }
 
-    Some((a_closest as u64, b_closest as u64))
+    let cost_step = 3 * step_a + step_b;
+    let k = match cost_step.signum() {
+        1 => lower?,
+        -1 => upper?,
+        _ => lower.or(upper).unwrap_or(0),
+    };
+
+    Some((a0 + k * step_a, b0 + k * step_b))
 }
 
 const EXTRA_OFFSET: isize = 10_000_000_000_000;
 
 impl ClawMachine {
-    fn do_part1(&self) -> Option<u64> {
+    /// Number of presses of `(button_a, button_b)` costing the least tokens, if any.
+    fn cheapest_presses(&self, target: Position) -> Option<(u64, u64)> {
+        #[cfg(feature = "linalg")]
+        if let Some(presses) = self.lapack_presses(target) {
+            return Some(presses);
+        }
+
+        self.exact_presses(target)
+    }
+
+    /// Solves the system with LAPACK, only trusting the rounded solution if it hits the target
+    /// exactly: anything else (singular or imprecise) is left to the exact solver.
+    #[cfg(feature = "linalg")]
+    fn lapack_presses(&self, target: Position) -> Option<(u64, u64)> {
         let mat: Array2<f64> = array![
             [self.button_a.0 as f64, self.button_b.0 as f64],
             [self.button_a.1 as f64, self.button_b.1 as f64]
         ];
 
-        let to_solve: Array1<f64> = array![self.target.0 as f64, self.target.1 as f64];
+        let to_solve: Array1<f64> = array![target.0 as f64, target.1 as f64];
+        let h = mat.solve_into(to_solve).ok()?;
+        let (a_presses, b_presses) = (float_presses(h[0])?, float_presses(h[1])?);
+        let (a, b) = (
+            isize::try_from(a_presses).ok()?,
+            isize::try_from(b_presses).ok()?,
+        );
+        let axis = |a_move: isize, b_move: isize| {
+            a_move.checked_mul(a)?.checked_add(b_move.checked_mul(b)?)
+        };
+        let reached = Position(
+            axis(self.button_a.0, self.button_b.0)?,
+            axis(self.button_a.1, self.button_b.1)?,
+        );
 
-        let h = mat
-            .solve_into(to_solve)
-            .expect("ndarray_linalg solve error");
+        (reached == target).then_some((a_presses, b_presses))
+    }
+
+    /// Exact number of presses of `(button_a, button_b)` costing the least tokens, if any.
+    fn exact_presses(&self, target: Position) -> Option<(u64, u64)> {
+        let (ax, ay) = (self.button_a.0 as i128, self.button_a.1 as i128);
+        let (bx, by) = (self.button_b.0 as i128, self.button_b.1 as i128);
+        let (tx, ty) = (target.0 as i128, target.1 as i128);
+
+        let determinant = ax * by - ay * bx;
+        let (a_presses, b_presses) = if determinant != 0 {
+            // Cramer's rule
+            let a_numerator = tx * by - ty * bx;
+            let b_numerator = ax * ty - ay * tx;
+            if a_numerator % determinant != 0 || b_numerator % determinant != 0 {
+                return None;
+            }
+
+            (a_numerator / determinant, b_numerator / determinant)
+        } else {
+            // both buttons move along the same line, which must also contain the target
+            if ax * ty - ay * tx != 0 || bx * ty - by * tx != 0 {
+                return None;
+            }
 
-        let (a_presses, b_presses) = get_integers_out(h[0], h[1])?;
+            if ax != 0 || bx != 0 {
+                cheapest_on_line(ax, bx, tx)?
+            } else if tx == 0 {
+                cheapest_on_line(ay, by, ty)?
+            } else {
+                return None;
+            }
+        };
 
-        Some(a_presses * 3 + b_presses)
+        Some((
+            u64::try_from(a_presses).ok()?,
+            u64::try_from(b_presses).ok()?,
+        ))
     }
 
-    fn do_part2(&self) -> Option<u64> {
-        let mat: Array2<f64> = array![
-            [self.button_a.0 as f64, self.button_b.0 as f64],
-            [self.button_a.1 as f64, self.button_b.1 as f64]
-        ];
+    fn tokens(&self, offset: isize) -> Option<u64> {
+        let target = self.target + Position(offset, offset);
+        let (a_presses, b_presses) = self.cheapest_presses(target)?;
 
-        let to_solve: Array1<f64> = array![
-            (self.target.0 + EXTRA_OFFSET) as f64,
-            (self.target.1 + EXTRA_OFFSET) as f64
-        ];
+        a_presses.checked_mul(3)?.checked_add(b_presses)
+    }
 
-        let h = mat
-            .solve_into(to_solve)
-            .expect("ndarray_linalg solve error");
+    fn do_part1(&self) -> Option<u64> {
+        self.tokens(0)
+    }
 
-        let (a_presses, b_presses) = get_integers_out(h[0], h[1])?;
+    fn do_part2(&self) -> Option<u64> {
+        self.tokens(EXTRA_OFFSET)
+    }
+}
 
-        Some(a_presses * 3 + b_presses)
+/// Rounded number of presses of a LAPACK solution, if it is one at all.
+#[cfg(feature = "linalg")]
+fn float_presses(presses: f64) -> Option<u64> {
+    let presses = presses.round();
+    // the range check also rejects NaN, and every integral f64 in it converts exactly
+    if !(0. ..u64::MAX as f64).contains(&presses) {
+        return None;
     }
+
+    Some(presses as u64)
 }
 
 #[aoc(day13, part1)]
@@ -134,6 +237,28 @@ fn part2(machines: &[ClawMachine]) -> u64 {
     machines.iter().filter_map(ClawMachine::do_part2).sum()
 }
 
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: u8 = 13;
+
+    type Input<'i> = Vec<ClawMachine>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,6 +281,45 @@ Prize: X=18641, Y=10279";
 
     #[test]
     fn part1_examples() {
-        assert_eq!(part1(&parse(EXAMPLE)), 480);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 480);
+    }
+
+    #[test]
+    fn part2_examples() {
+        assert_eq!(part2(&parse(EXAMPLE).unwrap()), 875318608908);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+6x\nPrize: X=8400, Y=5400";
+        assert_eq!(
+            parse(input).unwrap_err().to_string(),
+            "Day 13, line 2, column 19: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn collinear_buttons() {
+        let machine = |button_a, button_b, target| ClawMachine {
+            button_a,
+            button_b,
+            target,
+        };
+
+        // B alone is cheaper per step
+        let claw = machine(Position(2, 2), Position(1, 1), Position(10, 10));
+        assert_eq!(claw.cheapest_presses(claw.target), Some((0, 10)));
+        // A moves more than 3 times as far as B
+        let claw = machine(Position(4, 4), Position(1, 1), Position(10, 10));
+        assert_eq!(claw.cheapest_presses(claw.target), Some((2, 2)));
+        let claw = machine(Position(6, 3), Position(4, 2), Position(14, 7));
+        assert_eq!(claw.cheapest_presses(claw.target), Some((1, 2)));
+
+        let claw = machine(Position(2, 2), Position(4, 4), Position(5, 5));
+        assert_eq!(claw.cheapest_presses(claw.target), None);
+        let claw = machine(Position(2, 2), Position(4, 4), Position(6, 7));
+        assert_eq!(claw.cheapest_presses(claw.target), None);
+        let claw = machine(Position(0, 0), Position(0, 0), Position(0, 0));
+        assert_eq!(claw.cheapest_presses(claw.target), Some((0, 0)));
     }
 }
@@ -1,84 +1,26 @@
-use std::{error::Error, str::FromStr};
+use std::{
+    error::Error,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
 
 use fnv::FnvHashSet;
 use itertools::Itertools;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(isize, isize);
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
-
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
-
-impl std::ops::Mul<isize> for Position {
-    type Output = Position;
-
-    fn mul(self, rhs: isize) -> Self::Output {
-        Self(self.0 * rhs, self.1 * rhs)
-    }
-}
-
-impl std::ops::MulAssign<isize> for Position {
-    fn mul_assign(&mut self, rhs: isize) {
-        *self = *self * rhs;
-    }
-}
-
-impl Position {
-    fn wrap(&mut self, dimension: Position) {
-        self.0 = self.0.rem_euclid(dimension.0);
-        self.1 = self.1.rem_euclid(dimension.1);
-    }
-}
+use crate::{
+    grid::Position,
+    parse::{Parameters, ParseError},
+    solution::Solution,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Robot {
+pub struct Robot {
     position: Position,
     speed: Position,
 }
 
-impl FromStr for Position {
-    type Err = Box<dyn Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (left_num, right_num) = s
-            .split_once(',')
-            .ok_or("Could not split ',' from position")?;
-
-        Ok(Position(left_num.parse()?, right_num.parse()?))
-    }
-}
-
 impl FromStr for Robot {
     type Err = Box<dyn Error>;
 
@@ -106,16 +48,57 @@ impl Robot {
 
         robot_position
     }
+}
 
-    fn move_next_second(&mut self, dimension: Position) {
-        self.position += self.speed;
-        self.position.wrap(dimension);
-    }
+/// Size of the bathroom of the puzzle.
+const DIMENSION: Position = Position(101, 103);
+/// Size of the bathroom of the example, used when all robots fit in it.
+const EXAMPLE_DIMENSION: Position = Position(11, 7);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bathroom {
+    robots: Vec<Robot>,
+    dimension: Position,
 }
 
+/// Robots, one per line, after an optional `# size=<width>x<height>` line.
 #[aoc_generator(day14)]
-fn parse(input: &str) -> Result<Vec<Robot>, Box<dyn Error>> {
-    input.lines().map(|line| line.parse()).try_collect()
+fn parse(input: &str) -> Result<Bathroom, ParseError> {
+    let (parameters, robots) = Parameters::split(14, input, &["size"])?;
+    let robots = robots
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse::<Robot>()
+                .map_err(|error| ParseError::at(14, input, line, error))
+                .map(|robot| (line, robot))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fits = |dimension: Position, (_, robot): &(&str, Robot)| {
+        (0..dimension.0).contains(&robot.position.0) && (0..dimension.1).contains(&robot.position.1)
+    };
+    let dimension = match parameters.dimension("size")? {
+        Some(dimension) => dimension,
+        None if robots.iter().all(|robot| fits(EXAMPLE_DIMENSION, robot)) => EXAMPLE_DIMENSION,
+        None => DIMENSION,
+    };
+    if let Some((line, _)) = robots.iter().find(|robot| !fits(dimension, robot)) {
+        return Err(ParseError::at(
+            14,
+            input,
+            line,
+            format!(
+                "Robot is outside of the {}x{} bathroom",
+                dimension.0, dimension.1
+            ),
+        ));
+    }
+
+    Ok(Bathroom {
+        robots: robots.into_iter().map(|(_, robot)| robot).collect(),
+        dimension,
+    })
 }
 
 fn part1_in_dim(robots: &[Robot], dimension: Position) -> u64 {
@@ -148,67 +131,325 @@ fn part1_in_dim(robots: &[Robot], dimension: Position) -> u64 {
 }
 
 #[aoc(day14, part1)]
-fn part1(robots: &[Robot]) -> u64 {
-    part1_in_dim(robots, Position(101, 103))
-}
-
-const MAX_FRAME: u64 = 11_000;
-const CONTINUOUS_LENGTH: i32 = 10;
-fn do_part2(robots: &[Robot], dimension: Position) -> (Box<[Robot]>, u64) {
-    let mut robots = robots.to_owned().into_boxed_slice();
-    let mut positions = FnvHashSet::default();
-    for frame in 1..=MAX_FRAME {
-        positions.clear();
-        for robot in robots.iter_mut() {
-            robot.move_next_second(dimension);
-            positions.insert(robot.position);
-        }
+fn part1(bathroom: &Bathroom) -> u64 {
+    part1_in_dim(&bathroom.robots, bathroom.dimension)
+}
 
-        for line in 0..dimension.0 {
-            let mut continuous_count = 0;
-            for col in 0..dimension.1 {
-                if positions.contains(&Position(line, col)) {
-                    continuous_count += 1;
-                    if continuous_count >= CONTINUOUS_LENGTH {
-                        return (robots, frame);
-                    }
-                } else {
-                    continuous_count = 0;
-                }
-            }
+/// `n * sum(x²) - sum(x)²`, which is `n²` times the variance of `values`.
+fn scaled_variance(values: impl Iterator<Item = isize>) -> i128 {
+    let (n, sum, sum_of_squares) = values.fold((0_i128, 0_i128, 0_i128), |(n, sum, squares), x| {
+        (n + 1, sum + x as i128, squares + (x as i128).pow(2))
+    });
+
+    n * sum_of_squares - sum.pow(2)
+}
+
+/// The frame in `0..period` where `coordinate` of the robots varies the least, if it is clearly
+/// below the average of the period.
+fn least_variance_frame(
+    robots: &[Robot],
+    period: isize,
+    coordinate: impl Fn(&Robot) -> (isize, isize),
+) -> Option<isize> {
+    let variances = (0..period)
+        .map(|frame| {
+            scaled_variance(robots.iter().map(|robot| {
+                let (position, speed) = coordinate(robot);
+                (position + speed * frame).rem_euclid(period)
+            }))
+        })
+        .collect_vec();
+
+    let average = variances.iter().sum::<i128>() / variances.len() as i128;
+    let (frame, &variance) = variances
+        .iter()
+        .enumerate()
+        .min_by_key(|&(_, variance)| variance)?;
+
+    (variance * 4 < average * 3).then_some(frame as isize)
+}
+
+fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        return (a, 1, 0);
+    }
+
+    let (gcd, x, y) = extended_gcd(b, a % b);
+    (gcd, y, x - a / b * y)
+}
+
+/// Smallest `t >= 0` with `t = a mod m` and `t = b mod n`.
+fn chinese_remainder((a, m): (i64, i64), (b, n): (i64, i64)) -> Option<i64> {
+    let (gcd, x, _) = extended_gcd(m, n);
+    if (b - a) % gcd != 0 {
+        return None;
+    }
+
+    let lcm = m / gcd * n;
+    let t = a as i128 + (b - a) as i128 / gcd as i128 * x as i128 % (n / gcd) as i128 * m as i128;
+    Some(t.rem_euclid(lcm as i128) as i64)
+}
+
+fn lcm(a: isize, b: isize) -> isize {
+    a / extended_gcd(a as i64, b as i64).0 as isize * b
+}
+
+/// Number of robots with another robot right next to them.
+fn clustering_score(robots: &[Robot], dimension: Position, frame: isize) -> usize {
+    let positions = robots
+        .iter()
+        .map(|robot| robot.position_after_n_seconds(dimension, frame))
+        .collect::<FnvHashSet<_>>();
+
+    positions
+        .iter()
+        .filter(|position| {
+            position
+                .direct_neighbors()
+                .any(|neighbor| positions.contains(&neighbor))
+        })
+        .count()
+}
+
+/// The first frame where the robots draw a picture. The x coordinates repeat every `width`
+/// frames and the y coordinates every `height` frames, so the frame where each is the least
+/// spread out is found separately and they are combined with the Chinese remainder theorem.
+/// If either axis shows no clear picture, every frame until the robots loop is scored on how
+/// clustered they are.
+fn find_easter_egg(robots: &[Robot], dimension: Position) -> Option<isize> {
+    if robots.is_empty() {
+        return None;
+    }
+
+    let Position(width, height) = dimension;
+    let x_frame = least_variance_frame(robots, width, |robot| (robot.position.0, robot.speed.0));
+    let y_frame = least_variance_frame(robots, height, |robot| (robot.position.1, robot.speed.1));
+    if let Some((x_frame, y_frame)) = x_frame.zip(y_frame) {
+        let frame = chinese_remainder(
+            (x_frame as i64, width as i64),
+            (y_frame as i64, height as i64),
+        );
+        if let Some(frame) = frame {
+            return Some(frame as isize);
         }
     }
 
-    unreachable!("After {MAX_FRAME} frames, still haven't found anything");
+    most_clustered_frame(robots, dimension)
 }
 
-#[aoc(day14, part2)]
-fn part2(robots: &[Robot]) -> String {
-    let (robots, frame) = do_part2(robots, Position(101, 103));
-    let mut answer = format!("Frame {frame}:\n");
+fn most_clustered_frame(robots: &[Robot], dimension: Position) -> Option<isize> {
+    (0..lcm(dimension.0, dimension.1)).max_by_key(|&frame| {
+        (
+            clustering_score(robots, dimension, frame),
+            // first frame among the best
+            -frame,
+        )
+    })
+}
+
+/// The robots after `frame` seconds, one `#` per occupied tile.
+pub fn render(robots: &[Robot], dimension: Position, frame: isize) -> String {
     let positions = robots
         .iter()
-        .map(|robot| robot.position)
+        .map(|robot| robot.position_after_n_seconds(dimension, frame))
         .collect::<FnvHashSet<Position>>();
 
-    for line in 0..103 {
-        for col in 0..101 {
-            if positions.contains(&Position(col, line)) {
-                answer.push('#');
+    let mut picture = String::new();
+    for y in 0..dimension.1 {
+        for x in 0..dimension.0 {
+            picture.push(if positions.contains(&Position(x, y)) {
+                '#'
             } else {
-                answer.push(' ');
-            }
+                ' '
+            });
+        }
+
+        picture.push('\n');
+    }
+
+    picture
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    /// Plain PBM, black where there is at least one robot.
+    Bitmap,
+    /// Plain PGM, brighter with more robots on a cell.
+    Greymap,
+}
+
+impl ImageFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Bitmap => "pbm",
+            Self::Greymap => "pgm",
         }
+    }
+}
+
+const MAX_GREY: u32 = 255;
+/// Grey of the lines between the frames of a contact sheet.
+const SEPARATOR_GREY: u32 = 128;
+
+/// Number of robots on each cell at `frame`, row by row.
+fn density(robots: &[Robot], dimension: Position, frame: isize) -> Vec<u32> {
+    let mut density = vec![0; (dimension.0 * dimension.1) as usize];
+    for robot in robots {
+        let Position(x, y) = robot.position_after_n_seconds(dimension, frame);
+        density[(y * dimension.0 + x) as usize] += 1;
+    }
+
+    density
+}
+
+/// Writes a plain PBM (`P1`) or PGM (`P2`, with a maximum of [`MAX_GREY`]), wrapping lines
+/// before 70 characters.
+fn write_plain_image(
+    mut writer: impl Write,
+    format: ImageFormat,
+    (width, height): (usize, usize),
+    pixels: impl IntoIterator<Item = u32>,
+) -> io::Result<()> {
+    match format {
+        ImageFormat::Bitmap => writeln!(writer, "P1\n{width} {height}")?,
+        ImageFormat::Greymap => writeln!(writer, "P2\n{width} {height}\n{MAX_GREY}")?,
+    }
+
+    let mut line_length = 0;
+    for (index, pixel) in pixels.into_iter().enumerate() {
+        let pixel = pixel.to_string();
+        if index % width == 0 && index > 0 || line_length + pixel.len() >= 70 {
+            writeln!(writer)?;
+            line_length = 0;
+        } else if line_length > 0 {
+            write!(writer, " ")?;
+            line_length += 1;
+        }
+
+        write!(writer, "{pixel}")?;
+        line_length += pixel.len();
+    }
+
+    writeln!(writer)
+}
+
+fn pixel(format: ImageFormat, density: u32, max_density: u32) -> u32 {
+    match format {
+        ImageFormat::Bitmap => (density > 0) as u32,
+        ImageFormat::Greymap => density * MAX_GREY / max_density.max(1),
+    }
+}
+
+/// Writes the robots at `frame` as a plain PBM or PGM image, one pixel per cell.
+pub fn write_frame(
+    writer: impl Write,
+    format: ImageFormat,
+    robots: &[Robot],
+    dimension: Position,
+    frame: isize,
+) -> io::Result<()> {
+    let density = density(robots, dimension, frame);
+    let max_density = density.iter().copied().max().unwrap_or(0);
+
+    write_plain_image(
+        writer,
+        format,
+        (dimension.0 as usize, dimension.1 as usize),
+        density
+            .into_iter()
+            .map(|density| pixel(format, density, max_density)),
+    )
+}
+
+/// Writes every frame of `frames` to `directory/frame-<frame>.<pbm|pgm>`.
+pub fn export_frames(
+    directory: &Path,
+    format: ImageFormat,
+    robots: &[Robot],
+    dimension: Position,
+    frames: impl IntoIterator<Item = isize>,
+) -> io::Result<()> {
+    std::fs::create_dir_all(directory)?;
+    for frame in frames {
+        let path = directory.join(format!("frame-{frame:05}.{}", format.extension()));
+        let file = BufWriter::new(File::create(path)?);
+        write_frame(file, format, robots, dimension, frame)?;
+    }
+
+    Ok(())
+}
+
+/// Writes `frames` as a single plain PGM, tiled left to right and top to bottom in `columns`
+/// columns separated by grey lines. Grey levels are relative to the densest cell of all frames.
+pub fn write_contact_sheet(
+    writer: impl Write,
+    robots: &[Robot],
+    dimension: Position,
+    frames: impl IntoIterator<Item = isize>,
+    columns: usize,
+) -> io::Result<()> {
+    let tiles = frames
+        .into_iter()
+        .map(|frame| density(robots, dimension, frame))
+        .collect_vec();
+    let max_density = tiles.iter().flatten().copied().max().unwrap_or(0);
+
+    let (tile_width, tile_height) = (dimension.0 as usize, dimension.1 as usize);
+    let columns = columns.clamp(1, tiles.len().max(1));
+    let rows = tiles.len().div_ceil(columns);
+    let width = columns * (tile_width + 1) - 1;
+    let height = (rows * (tile_height + 1)).saturating_sub(1);
+
+    let pixels = (0..height).cartesian_product(0..width).map(|(y, x)| {
+        let (row, tile_y) = (y / (tile_height + 1), y % (tile_height + 1));
+        let (column, tile_x) = (x / (tile_width + 1), x % (tile_width + 1));
+        if tile_y == tile_height || tile_x == tile_width {
+            return SEPARATOR_GREY;
+        }
+
+        tiles.get(row * columns + column).map_or(0, |tile| {
+            pixel(
+                ImageFormat::Greymap,
+                tile[tile_y * tile_width + tile_x],
+                max_density,
+            )
+        })
+    });
+
+    write_plain_image(writer, ImageFormat::Greymap, (width, height), pixels)
+}
+
+#[aoc(day14, part2)]
+fn part2(bathroom: &Bathroom) -> Option<isize> {
+    find_easter_egg(&bathroom.robots, bathroom.dimension)
+}
+
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: u8 = 14;
 
-        answer.push('\n');
+    type Input<'i> = Bathroom;
+    type Part1 = u64;
+    type Part2 = Option<isize>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
     }
 
-    answer
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::Random;
 
     const EXAMPLE: &str = "p=0,4 v=3,-3
 p=6,3 v=-1,-3
@@ -225,6 +466,154 @@ p=9,5 v=-3,-3";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1_in_dim(&parse(EXAMPLE).unwrap(), Position(11, 7)), 12);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 12);
+    }
+
+    #[test]
+    fn bathroom_size() {
+        assert_eq!(parse(EXAMPLE).unwrap().dimension, EXAMPLE_DIMENSION);
+        assert_eq!(parse("p=11,4 v=3,-3").unwrap().dimension, DIMENSION);
+
+        let bathroom = parse(&format!("# size=11x7\n{EXAMPLE}")).unwrap();
+        assert_eq!(bathroom.dimension, Position(11, 7));
+        assert_eq!(bathroom.robots.len(), 12);
+        assert_eq!(
+            parse("# size=20x20\np=0,4 v=3,-3").unwrap().dimension,
+            Position(20, 20)
+        );
+
+        assert_eq!(
+            parse("# size=5x5\np=1,1 v=0,0\np=5,0 v=1,1")
+                .unwrap_err()
+                .to_string(),
+            "Day 14, line 3, column 1: Robot is outside of the 5x5 bathroom"
+        );
+    }
+
+    /// Robots drawing a filled square at `frame`, among as many robots moving at random.
+    fn hidden_square(dimension: Position, frame: isize, seed: u64) -> Vec<Robot> {
+        let mut random = Random::new(seed);
+        let mut random = |max: isize| random.below(max as u64) as isize;
+
+        let mut robots = Vec::new();
+        for (x, y) in (0..15).cartesian_product(0..15) {
+            let speed = Position(random(201) - 100, random(201) - 100);
+            let mut position = Position(40 + x, 50 + y) - speed * frame;
+            position.wrap(dimension);
+            robots.push(Robot { position, speed });
+        }
+
+        for _ in 0..225 {
+            let position = Position(random(dimension.0), random(dimension.1));
+            let speed = Position(random(201) - 100, random(201) - 100);
+            robots.push(Robot { position, speed });
+        }
+
+        robots
+    }
+
+    #[test]
+    fn finds_easter_egg() {
+        for (dimension, frame) in [
+            (Position(101, 103), 6587),
+            (Position(101, 103), 12),
+            (Position(79, 83), 4000),
+        ] {
+            let robots = hidden_square(dimension, frame, frame as u64);
+            assert_eq!(find_easter_egg(&robots, dimension), Some(frame));
+        }
+
+        // periods that are not coprime, with too many y speeds sharing a factor with the height
+        // for the y variance to stand out, so the clustering score finds it
+        let dimension = Position(90, 60);
+        let robots = hidden_square(dimension, 100, 3);
+        assert_eq!(find_easter_egg(&robots, dimension), Some(100));
+        assert!(render(&robots, dimension, 100).contains(&"#".repeat(15)));
+
+        assert_eq!(most_clustered_frame(&robots, dimension), Some(100));
+
+        let bathroom = Bathroom { robots, dimension };
+        assert_eq!(part2(&bathroom), Some(100));
+    }
+
+    #[test]
+    fn frame_images() {
+        let robots = [
+            Robot {
+                position: Position(0, 0),
+                speed: Position(1, 0),
+            },
+            Robot {
+                position: Position(2, 1),
+                speed: Position(0, 0),
+            },
+            Robot {
+                position: Position(1, 1),
+                speed: Position(1, 0),
+            },
+        ];
+        let dimension = Position(3, 2);
+        let image = |format, frame| {
+            let mut image = Vec::new();
+            write_frame(&mut image, format, &robots, dimension, frame).unwrap();
+            String::from_utf8(image).unwrap()
+        };
+
+        assert_eq!(image(ImageFormat::Bitmap, 0), "P1\n3 2\n1 0 0\n0 1 1\n");
+        assert_eq!(
+            image(ImageFormat::Greymap, 1),
+            "P2\n3 2\n255\n0 127 0\n0 0 255\n"
+        );
+        assert_eq!(
+            image(ImageFormat::Greymap, 0),
+            "P2\n3 2\n255\n255 0 0\n0 255 255\n"
+        );
+
+        let mut sheet = Vec::new();
+        write_contact_sheet(&mut sheet, &robots, dimension, 0..3, 2).unwrap();
+        assert_eq!(
+            String::from_utf8(sheet).unwrap(),
+            "P2\n7 5\n255
+127 0 0 128 0 127 0
+0 127 127 128 0 0 255
+128 128 128 128 128 128 128
+0 0 127 128 0 0 0
+127 0 127 128 0 0 0
+"
+        );
+
+        let directory = std::env::temp_dir().join(format!("day14-frames-{}", std::process::id()));
+        export_frames(&directory, ImageFormat::Bitmap, &robots, dimension, 0..3).unwrap();
+        let frame = std::fs::read_to_string(directory.join("frame-00002.pbm")).unwrap();
+        assert_eq!(frame, image(ImageFormat::Bitmap, 2));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn long_image_lines() {
+        let robots = [Robot {
+            position: Position(0, 0),
+            speed: Position(0, 0),
+        }];
+        let mut image = Vec::new();
+        write_frame(
+            &mut image,
+            ImageFormat::Greymap,
+            &robots,
+            Position(101, 2),
+            0,
+        )
+        .unwrap();
+        let image = String::from_utf8(image).unwrap();
+        assert!(image.lines().all(|line| line.len() < 70));
+        assert_eq!(image.split_whitespace().count(), 4 + 202);
+    }
+
+    #[test]
+    fn chinese_remainder_theorem() {
+        assert_eq!(chinese_remainder((2, 3), (3, 5)), Some(8));
+        assert_eq!(chinese_remainder((0, 101), (0, 103)), Some(0));
+        assert_eq!(chinese_remainder((3, 4), (1, 6)), Some(7));
+        assert_eq!(chinese_remainder((1, 4), (2, 6)), None);
     }
 }
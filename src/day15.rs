@@ -2,56 +2,11 @@ use std::{collections::VecDeque, fmt::Write};
 
 use fnv::FnvHashMap;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(isize, isize);
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
-
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
-
-impl std::ops::Mul<isize> for Position {
-    type Output = Position;
-
-    fn mul(self, rhs: isize) -> Self::Output {
-        Self(self.0 * rhs, self.1 * rhs)
-    }
-}
-
-impl std::ops::MulAssign<isize> for Position {
-    fn mul_assign(&mut self, rhs: isize) {
-        *self = *self * rhs;
-    }
-}
+use crate::{
+    grid::{Grid, Position},
+    parse::ParseError,
+    solution::Solution,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Move {
@@ -104,6 +59,24 @@ enum Tile {
     BoxRight,
 }
 
+impl TryFrom<u8> for Tile {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'#' => Ok(Self::Wall),
+            b'.' => Ok(Self::FreeSpace),
+            b'O' => Ok(Self::Box),
+            b'[' => Ok(Self::BoxLeft),
+            b']' => Ok(Self::BoxRight),
+            _ => Err(format!(
+                "Invalid char '{}': was not any of '#', '.', 'O', '[', ']' nor '@'",
+                value as char
+            )),
+        }
+    }
+}
+
 impl std::fmt::Display for Tile {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -122,22 +95,17 @@ impl std::fmt::Display for Tile {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Map {
-    map: Vec<Vec<Tile>>,
+    map: Grid<Tile>,
     robot_position: Position,
 }
 
 impl Map {
     fn sum_box_gps(&self) -> u64 {
-        let mut sum = 0;
-        for (row_idx, row) in self.map.iter().enumerate() {
-            for (col_idx, &tile) in row.iter().enumerate() {
-                if matches!(tile, Tile::Box | Tile::BoxLeft) {
-                    sum += (row_idx * 100 + col_idx) as u64;
-                }
-            }
-        }
-
-        sum
+        self.map
+            .iter()
+            .filter(|(_, tile)| matches!(tile, Tile::Box | Tile::BoxLeft))
+            .map(|(position, _)| (position.0 * 100 + position.1) as u64)
+            .sum()
     }
 
     fn perform_move_part2(&mut self, mv: Move) {
@@ -262,152 +230,124 @@ impl Map {
             other => panic!("Tile '{other}' encountered in part 1"),
         }
     }
-
-    fn get(&self, index: Position) -> Option<&Tile> {
-        if index.0 < 0 || index.1 < 0 {
-            return None;
-        }
-
-        self.map.get(index.0 as usize)?.get(index.1 as usize)
-    }
-
-    fn get_mut(&mut self, index: Position) -> Option<&mut Tile> {
-        if index.0 < 0 || index.1 < 0 {
-            return None;
-        }
-
-        self.map
-            .get_mut(index.0 as usize)?
-            .get_mut(index.1 as usize)
-    }
-
-    fn from_iter_part2<'s, I: IntoIterator<Item = &'s str>>(iter: I) -> Self {
-        let mut bot_position = None;
-        let map = iter
-            .into_iter()
-            .enumerate()
-            .map(|(row_idx, line)| {
-                line.trim()
-                    .as_bytes()
-                    .iter()
-                    .enumerate()
-                    .flat_map(|(col_idx, c)| match c {
-                        b'#' => [Tile::Wall, Tile::Wall],
-                        b'.' => [Tile::FreeSpace, Tile::FreeSpace],
-                        b'O' => [Tile::BoxLeft, Tile::BoxRight],
-                        b'@' => {
-                            bot_position = Some(Position(row_idx as isize, (col_idx * 2) as isize));
-                            [Tile::FreeSpace, Tile::FreeSpace]
-                        }
-                        _ => panic!(
-                            "Invalid char '{}': was not any of '#', '.', 'O' nor '@'",
-                            *c as char
-                        ),
-                    })
-                    .collect()
-            })
-            .collect();
-
-        Self {
-            map,
-            robot_position: bot_position.expect("Did not find '@' in input"),
-        }
-    }
 }
 
 impl std::ops::Index<Position> for Map {
     type Output = Tile;
 
     fn index(&self, index: Position) -> &Self::Output {
-        self.get(index)
-            .unwrap_or_else(|| panic!("Could not index position {index} in map"))
+        &self.map[index]
     }
 }
 
 impl std::ops::IndexMut<Position> for Map {
     fn index_mut(&mut self, index: Position) -> &mut Self::Output {
-        self.get_mut(index)
-            .unwrap_or_else(|| panic!("Could not index position {index} in map"))
+        &mut self.map[index]
     }
 }
 
 impl std::fmt::Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in 0..(self.map.len() as isize) {
-            for col in 0..(self.map[row as usize].len() as isize) {
-                if Position(row, col) == self.robot_position {
-                    f.write_char('@')?;
-                    continue;
-                }
-
-                write!(f, "{}", self[Position(row, col)])?;
+        for (position, tile) in self.map.iter() {
+            if position == self.robot_position {
+                f.write_char('@')?;
+            } else {
+                write!(f, "{tile}")?;
             }
 
-            writeln!(f)?;
+            if position.1 as usize == self.map.width() - 1 {
+                writeln!(f)?;
+            }
         }
 
         Ok(())
     }
 }
 
-impl<'s> FromIterator<&'s str> for Map {
-    fn from_iter<T: IntoIterator<Item = &'s str>>(iter: T) -> Self {
-        let mut bot_position = None;
-        let map = iter
-            .into_iter()
-            .enumerate()
-            .map(|(row_idx, line)| {
-                line.trim()
-                    .as_bytes()
-                    .iter()
-                    .enumerate()
-                    .map(|(col_idx, c)| match c {
-                        b'#' => Tile::Wall,
-                        b'.' => Tile::FreeSpace,
-                        b'O' => Tile::Box,
-                        b'@' => {
-                            bot_position = Some(Position(row_idx as isize, col_idx as isize));
-                            Tile::FreeSpace
-                        }
-                        _ => panic!(
-                            "Invalid char '{}': was not any of '#', '.', 'O' nor '@'",
-                            *c as char
-                        ),
-                    })
-                    .collect()
-            })
-            .collect();
-
-        Self {
-            map,
-            robot_position: bot_position.expect("Did not find '@' in input"),
+impl Map {
+    fn parse(map: &str) -> Result<Self, ParseError> {
+        let mut robot_position = None;
+        let map_grid = Grid::parse_with(map, |position, c| {
+            if c == b'@' {
+                robot_position = Some(position);
+                return Ok(Tile::FreeSpace);
+            }
+
+            Tile::try_from(c)
+        })
+        .map_err(|error| ParseError::from_grid(15, error))?;
+
+        Ok(Self {
+            map: map_grid,
+            robot_position: robot_position
+                .ok_or_else(|| ParseError::at_end(15, map, "Did not find '@' in the map"))?,
+        })
+    }
+}
+
+fn parse_moves(input: &str, moves: &str) -> Result<Vec<Move>, ParseError> {
+    moves
+        .char_indices()
+        .filter(|(_, c)| !c.is_whitespace())
+        .map(|(idx, c)| {
+            u8::try_from(c)
+                .map_err(|_| "Move is not an ASCII char")
+                .and_then(Move::try_from)
+                .map_err(|error| ParseError::at(15, input, &moves[idx..], error))
+        })
+        .collect()
+}
+
+fn widen_map(map: &str) -> String {
+    let mut widened = String::with_capacity(map.len() * 2);
+    for c in map.chars() {
+        match c {
+            '#' => widened.push_str("##"),
+            '.' => widened.push_str(".."),
+            'O' => widened.push_str("[]"),
+            '@' => widened.push_str("@."),
+            other => widened.push(other),
         }
     }
+
+    widened
 }
 
-fn parse_part1(input: &str) -> (Map, Vec<Move>) {
-    let mut lines = input.lines();
-    let map = lines.by_ref().take_while(|line| !line.is_empty()).collect();
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warehouse {
+    map: Map,
+    /// The map of part 2, twice as wide.
+    wide_map: Map,
+    moves: Vec<Move>,
+}
 
-    (
+#[aoc_generator(day15)]
+fn parse(input: &str) -> Result<Warehouse, ParseError> {
+    // the map ends at the first empty line, whatever the line endings
+    let map_len = input
+        .split_inclusive('\n')
+        .take_while(|line| !line.trim().is_empty())
+        .map(str::len)
+        .sum();
+    let (map_input, moves) = input.split_at(map_len);
+    let map = Map::parse(map_input)?;
+    let wide_map = Map::parse(&widen_map(map_input)).map_err(|mut error| {
+        // point to the column of the original map
+        error.column = (error.column - 1) / 2 + 1;
+        error
+    })?;
+
+    Ok(Warehouse {
         map,
-        lines
-            .flat_map(|line| {
-                line.as_bytes()
-                    .iter()
-                    .filter_map(|c| match Move::try_from(*c) {
-                        Ok(mv) => Some(mv),
-                        Err(_) => None,
-                    })
-            })
-            .collect(),
-    )
+        wide_map,
+        moves: parse_moves(input, moves)?,
+    })
 }
 
 #[aoc(day15, part1)]
-fn part1(input: &str) -> u64 {
-    let (mut map, moves) = parse_part1(input);
-    for mv in moves {
+fn part1(warehouse: &Warehouse) -> u64 {
+    let mut map = warehouse.map.clone();
+    for &mv in &warehouse.moves {
         map.perform_move_part1(mv);
         // println!("Move {mv:?}:");
         // println!("{}", &map);
@@ -416,29 +356,10 @@ fn part1(input: &str) -> u64 {
     map.sum_box_gps()
 }
 
-fn parse_part2(input: &str) -> (Map, Vec<Move>) {
-    let mut lines = input.lines();
-    let map = Map::from_iter_part2(lines.by_ref().take_while(|line| !line.is_empty()));
-
-    (
-        map,
-        lines
-            .flat_map(|line| {
-                line.as_bytes()
-                    .iter()
-                    .filter_map(|c| match Move::try_from(*c) {
-                        Ok(mv) => Some(mv),
-                        Err(_) => None,
-                    })
-            })
-            .collect(),
-    )
-}
-
 #[aoc(day15, part2)]
-fn part2(input: &str) -> u64 {
-    let (mut map, moves) = parse_part2(input);
-    for mv in moves {
+fn part2(warehouse: &Warehouse) -> u64 {
+    let mut map = warehouse.wide_map.clone();
+    for &mv in &warehouse.moves {
         map.perform_move_part2(mv);
     }
 
@@ -446,6 +367,28 @@ fn part2(input: &str) -> u64 {
     map.sum_box_gps()
 }
 
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: u8 = 15;
+
+    type Input<'i> = Warehouse;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -485,12 +428,30 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(EXAMPLE1), 2028);
-        assert_eq!(part1(EXAMPLE2), 10092);
+        assert_eq!(part1(&parse(EXAMPLE1).unwrap()), 2028);
+        assert_eq!(part1(&parse(EXAMPLE2).unwrap()), 10092);
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(EXAMPLE2), 9021);
+        assert_eq!(part2(&parse(EXAMPLE2).unwrap()), 9021);
+    }
+
+    #[test]
+    fn crlf_input() {
+        let crlf = EXAMPLE2.replace('\n', "\r\n");
+        assert_eq!(parse(&crlf), parse(EXAMPLE2));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("####\n#@x#\n####\n\n<>").unwrap_err().to_string(),
+            "Day 15, line 2, column 3: Invalid cell at (1, 2): Invalid char 'x': was not any of '#', '.', 'O', '[', ']' nor '@'"
+        );
+        assert_eq!(
+            parse("####\n#@.#\n####\n\n<>\n^x").unwrap_err().to_string(),
+            "Day 15, line 6, column 2: u8's corresponding ASCII char was not any of 'v' '<' '^' or '>'"
+        );
     }
 }
@@ -3,8 +3,10 @@ use std::fmt::Write;
 
 use itertools::Itertools;
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Instruction {
+pub enum Instruction {
     Adv,
     Bxl,
     Bst,
@@ -73,205 +75,567 @@ impl From<Instruction> for u64 {
 }
 
 #[aoc_generator(day17)]
-fn parse(input: &str) -> (Vec<Instruction>, (u64, u64, u64)) {
+fn parse(input: &str) -> Result<(Vec<Instruction>, Registers), ParseError> {
     let mut lines = input.lines();
     let regs = lines
         .by_ref()
         .take(3)
         .map(|line| {
-            let (_, num) = line
-                .split_once(':')
-                .expect("Register line did not contain a ':'");
-            num.trim()
-                .parse::<u64>()
-                .expect("Could not parse register value")
+            let (_, num) = line.split_once(':').ok_or_else(|| {
+                ParseError::at(17, input, line, "Register line did not contain a ':'")
+            })?;
+            let num = num.trim();
+            num.parse::<u64>()
+                .map_err(|error| ParseError::at(17, input, num, error))
         })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
         .collect_tuple()
-        .expect("Could not collect into tuple of 3 elements");
+        .ok_or_else(|| ParseError::at_end(17, input, "Expected 3 register lines"))?;
+
+    let program_line = lines
+        .nth(1)
+        .ok_or_else(|| ParseError::at_end(17, input, "No program line"))?;
 
-    (parse_program(lines.nth(1).expect("No program line")), regs)
+    Ok((parse_program(input, program_line)?, regs))
 }
 
-fn parse_program(program_line: &str) -> Vec<Instruction> {
-    let program_digits = program_line
-        .strip_prefix("Program: ")
-        .expect("Did not find \"Program: \" at the start of the program line");
+fn parse_program(input: &str, program_line: &str) -> Result<Vec<Instruction>, ParseError> {
+    let program_digits = program_line.strip_prefix("Program: ").ok_or_else(|| {
+        ParseError::at(
+            17,
+            input,
+            program_line,
+            "Expected \"Program: \" at the start of the line",
+        )
+    })?;
     program_digits
         .split(',')
-        .map(|digit| digit.parse().expect("Could not parse instruction"))
+        .map(|digit| {
+            digit
+                .parse()
+                .map_err(|error| ParseError::at(17, input, digit, error))
+        })
         .collect()
 }
 
 #[aoc(day17, part1)]
-fn part1(input: &(Vec<Instruction>, (u64, u64, u64))) -> String {
-    let result = execute(&input.0, input.1);
-    let mut out = format!("{}", result[0]);
+fn part1(input: &(Vec<Instruction>, (u64, u64, u64))) -> Result<String, MachineError> {
+    let mut machine = Machine::new(&input.0, input.1);
+    Ok(machine.run(None)?.iter().join(","))
+}
+
+pub type Registers = (u64, u64, u64);
 
-    for c in result.into_iter().skip(1) {
-        write!(out, ",{c}").expect("Failed to write a u8 to a String");
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MachineError {
+    InvalidComboOperand { instr_ptr: usize },
+    MissingOperand { instr_ptr: usize },
+    StepLimitReached { steps: usize },
+}
+
+impl std::fmt::Display for MachineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidComboOperand { instr_ptr } => {
+                write!(
+                    f,
+                    "Combo operand 7 is not valid (instruction at {instr_ptr})"
+                )
+            }
+            Self::MissingOperand { instr_ptr } => {
+                write!(f, "Instruction at {instr_ptr} has no operand")
+            }
+            Self::StepLimitReached { steps } => {
+                write!(f, "Program did not halt after {steps} steps")
+            }
+        }
     }
+}
+
+impl Error for MachineError {}
 
-    out
+/// Outcome of [`Machine::step`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    Continue,
+    Output(u8),
+    Halted,
+}
+
+/// State of the machine right before it executes an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceEntry {
+    pub instr_ptr: usize,
+    pub instruction: Instruction,
+    pub operand: Instruction,
+    pub registers: Registers,
 }
 
-fn combo_op(registers: &(u64, u64, u64), combo: Instruction) -> u64 {
-    let val: u8 = combo.into();
-    match val {
-        0..=3 => val as u64,
-        4 => registers.0,
-        5 => registers.1,
-        6 => registers.2,
-        7 => panic!("combo operand 7 is not valid"),
-        _ => unreachable!("Instruction::into<u8>() returned a value > 7"),
+impl std::fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:>3}: A={} B={} C={} | {}",
+            self.instr_ptr,
+            self.registers.0,
+            self.registers.1,
+            self.registers.2,
+            pseudo_code(self.instruction, self.operand)
+        )
     }
 }
 
-fn execute(instructions: &[Instruction], mut registers: (u64, u64, u64)) -> Vec<u8> {
-    let mut result = vec![];
-    let mut instr_ptr = 0;
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Machine<'p> {
+    program: &'p [Instruction],
+    registers: Registers,
+    instr_ptr: usize,
+    output: Vec<u8>,
+    steps: usize,
+}
+
+impl<'p> Machine<'p> {
+    pub fn new(program: &'p [Instruction], registers: Registers) -> Self {
+        Self {
+            program,
+            registers,
+            instr_ptr: 0,
+            output: vec![],
+            steps: 0,
+        }
+    }
+
+    pub fn registers(&self) -> Registers {
+        self.registers
+    }
+
+    pub fn instr_ptr(&self) -> usize {
+        self.instr_ptr
+    }
+
+    /// Values output so far.
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.instr_ptr >= self.program.len()
+    }
 
-    while let Some(&instr) = instructions.get(instr_ptr) {
+    fn combo_operand(&self, combo: Instruction) -> Result<u64, MachineError> {
+        let val: u8 = combo.into();
+        match val {
+            0..=3 => Ok(val as u64),
+            4 => Ok(self.registers.0),
+            5 => Ok(self.registers.1),
+            6 => Ok(self.registers.2),
+            _ => Err(MachineError::InvalidComboOperand {
+                instr_ptr: self.instr_ptr,
+            }),
+        }
+    }
+
+    fn current_instruction(&self) -> Result<Option<(Instruction, Instruction)>, MachineError> {
+        let Some(&instr) = self.program.get(self.instr_ptr) else {
+            return Ok(None);
+        };
+
+        let &operand =
+            self.program
+                .get(self.instr_ptr + 1)
+                .ok_or(MachineError::MissingOperand {
+                    instr_ptr: self.instr_ptr,
+                })?;
+
+        Ok(Some((instr, operand)))
+    }
+
+    /// Executes the instruction at the instruction pointer, if the program did not halt yet.
+    pub fn step(&mut self) -> Result<Step, MachineError> {
+        let Some((instr, operand)) = self.current_instruction()? else {
+            return Ok(Step::Halted);
+        };
+
+        let mut step = Step::Continue;
+        let mut next_instr_ptr = self.instr_ptr + 2;
         match instr {
             Instruction::Adv => {
-                registers.0 >>= combo_op(&registers, instructions[instr_ptr + 1]);
-                instr_ptr += 2;
-            }
-            Instruction::Bxl => {
-                registers.1 ^= u64::from(instructions[instr_ptr + 1]);
-                instr_ptr += 2;
-            }
-            Instruction::Bst => {
-                registers.1 = combo_op(&registers, instructions[instr_ptr + 1]) & 0b111;
-                instr_ptr += 2;
+                self.registers.0 =
+                    divide_by_power_of_two(self.registers.0, self.combo_operand(operand)?)
             }
+            Instruction::Bxl => self.registers.1 ^= u64::from(operand),
+            Instruction::Bst => self.registers.1 = self.combo_operand(operand)? & 0b111,
             Instruction::Jnz => {
-                if registers.0 == 0 {
-                    instr_ptr += 2;
-                } else {
-                    instr_ptr = u64::from(instructions[instr_ptr + 1]) as usize;
+                if self.registers.0 != 0 {
+                    next_instr_ptr = u64::from(operand) as usize;
                 }
             }
-            Instruction::Bxc => {
-                registers.1 ^= registers.2;
-                instr_ptr += 2;
-            }
+            Instruction::Bxc => self.registers.1 ^= self.registers.2,
             Instruction::Out => {
-                result.push((combo_op(&registers, instructions[instr_ptr + 1]) & 0b111) as u8);
-                instr_ptr += 2;
+                let value = (self.combo_operand(operand)? & 0b111) as u8;
+                self.output.push(value);
+                step = Step::Output(value);
             }
             Instruction::Bdv => {
-                registers.1 = registers.0 >> combo_op(&registers, instructions[instr_ptr + 1]);
-                instr_ptr += 2;
+                self.registers.1 =
+                    divide_by_power_of_two(self.registers.0, self.combo_operand(operand)?)
             }
             Instruction::Cdv => {
-                registers.2 = registers.0 >> combo_op(&registers, instructions[instr_ptr + 1]);
-                instr_ptr += 2;
+                self.registers.2 =
+                    divide_by_power_of_two(self.registers.0, self.combo_operand(operand)?)
+            }
+        }
+
+        self.instr_ptr = next_instr_ptr;
+        self.steps += 1;
+        Ok(step)
+    }
+
+    /// Runs until the program halts, failing once `step_limit` instructions were executed.
+    pub fn run(&mut self, step_limit: Option<usize>) -> Result<&[u8], MachineError> {
+        self.run_traced(step_limit, |_| ())
+    }
+
+    /// Same as [`Machine::run`], calling `trace` before every instruction.
+    pub fn run_traced<F>(
+        &mut self,
+        step_limit: Option<usize>,
+        mut trace: F,
+    ) -> Result<&[u8], MachineError>
+    where
+        F: FnMut(&TraceEntry),
+    {
+        while !self.is_halted() {
+            if step_limit.is_some_and(|limit| self.steps >= limit) {
+                return Err(MachineError::StepLimitReached { steps: self.steps });
+            }
+
+            if let Some((instruction, operand)) = self.current_instruction()? {
+                trace(&TraceEntry {
+                    instr_ptr: self.instr_ptr,
+                    instruction,
+                    operand,
+                    registers: self.registers,
+                });
             }
+
+            self.step()?;
         }
+
+        Ok(&self.output)
+    }
+}
+
+/// `value / 2^exponent`, which is 0 once the exponent is as large as the register.
+fn divide_by_power_of_two(value: u64, exponent: u64) -> u64 {
+    u32::try_from(exponent)
+        .ok()
+        .and_then(|exponent| value.checked_shr(exponent))
+        .unwrap_or(0)
+}
+
+fn combo_operand_name(combo: Instruction) -> String {
+    match u8::from(combo) {
+        val @ 0..=3 => val.to_string(),
+        4 => "A".to_owned(),
+        5 => "B".to_owned(),
+        6 => "C".to_owned(),
+        _ => "<invalid combo 7>".to_owned(),
+    }
+}
+
+fn pseudo_code(instruction: Instruction, operand: Instruction) -> String {
+    let literal = u8::from(operand);
+    match instruction {
+        Instruction::Adv => format!("A = A >> {}", combo_operand_name(operand)),
+        Instruction::Bxl => format!("B = B ^ {literal}"),
+        Instruction::Bst => format!("B = {} % 8", combo_operand_name(operand)),
+        Instruction::Jnz => format!("if A != 0 jump {literal}"),
+        Instruction::Bxc => "B = B ^ C".to_owned(),
+        Instruction::Out => format!("out {} % 8", combo_operand_name(operand)),
+        Instruction::Bdv => format!("B = A >> {}", combo_operand_name(operand)),
+        Instruction::Cdv => format!("C = A >> {}", combo_operand_name(operand)),
+    }
+}
+
+/// One line of pseudo code per instruction, prefixed by its address.
+pub fn disassemble(program: &[Instruction]) -> String {
+    let mut result = String::new();
+    for (instr_ptr, chunk) in program.chunks(2).enumerate() {
+        let line = match chunk {
+            &[instruction, operand] => pseudo_code(instruction, operand),
+            _ => format!("{:?} <missing operand>", chunk[0]),
+        };
+
+        writeln!(result, "{:>3}: {line}", instr_ptr * 2).expect("Failed to write to a String");
     }
 
     result
 }
 
 #[aoc(day17, part2)]
-fn part2(input: &(Vec<Instruction>, (u64, u64, u64))) -> u64 {
-    part2_logic(&input.0, (0, input.1 .1, input.1 .2), 0)
+fn part2(input: &(Vec<Instruction>, (u64, u64, u64))) -> Result<u64, SolverError> {
+    let target = input.0.iter().map(|&instr| u8::from(instr)).collect_vec();
+    find_register_a(&input.0, &target)
 }
 
-fn part2_logic(instructions: &[Instruction], registers: (u64, u64, u64), on_instr: usize) -> u64 {
-    let mut min_found = u64::MAX;
-    for i in 0..=7 {
-        let reg_a = registers.0 | (i << (3 * on_instr));
-        let registers = (reg_a, registers.1, registers.2);
-        if on_instr >= 3 && on_instr < instructions.len() {
-            if (reg_a >> (3 * 3)) != 0
-                && !first_n_instructions_correct(instructions, registers, on_instr - 3)
-            {
-                continue;
-            }
-        } else if on_instr == instructions.len() {
-            if !first_n_instructions_correct(instructions, registers, instructions.len()) {
-                continue;
-            }
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SolverError {
+    MissingFinalJump,
+    UnexpectedJump {
+        instr_ptr: usize,
+    },
+    ShiftCount {
+        count: usize,
+    },
+    NonLiteralShift {
+        instr_ptr: usize,
+    },
+    ReadBeforeWrite {
+        instr_ptr: usize,
+        register: char,
+    },
+    NoOutput,
+    TargetLength {
+        target_len: usize,
+        outputs_per_iteration: usize,
+    },
+    Overflow {
+        bits: usize,
+    },
+    Machine(MachineError),
+    NoSolution,
+}
 
-            return reg_a;
+impl std::fmt::Display for SolverError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFinalJump => write!(f, "Program does not end with \"jnz 0\""),
+            Self::UnexpectedJump { instr_ptr } => write!(
+                f,
+                "Jump at {instr_ptr}: only a single jump back to the start is supported"
+            ),
+            Self::ShiftCount { count } => write!(
+                f,
+                "Program shifts A {count} times per loop, expected exactly once"
+            ),
+            Self::NonLiteralShift { instr_ptr } => write!(
+                f,
+                "Shift of A at {instr_ptr} is not by a literal between 1 and 3"
+            ),
+            Self::ReadBeforeWrite {
+                instr_ptr,
+                register,
+            } => write!(
+                f,
+                "Register {register} is read at {instr_ptr} before being written in the loop"
+            ),
+            Self::NoOutput => write!(f, "Program loop does not output anything"),
+            Self::TargetLength {
+                target_len,
+                outputs_per_iteration,
+            } => write!(
+                f,
+                "Target of length {target_len} is not a multiple of the {outputs_per_iteration} outputs per loop"
+            ),
+            Self::Overflow { bits } => {
+                write!(f, "Register A would need {bits} bits, which does not fit in a u64")
+            }
+            Self::Machine(error) => write!(f, "Program failed while searching: {error}"),
+            Self::NoSolution => write!(f, "No value of register A produces the target output"),
         }
+    }
+}
 
-        let reg_a = part2_logic(instructions, registers, on_instr + 1);
-        if reg_a < min_found {
-            min_found = reg_a;
-        }
+impl Error for SolverError {}
+
+impl From<MachineError> for SolverError {
+    fn from(value: MachineError) -> Self {
+        Self::Machine(value)
     }
+}
 
-    min_found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ProgramShape {
+    shift: u32,
+    outputs_per_iteration: usize,
 }
 
-fn first_n_instructions_correct(
-    instructions: &[Instruction],
-    mut registers: (u64, u64, u64),
-    to_check: usize,
-) -> bool {
-    let mut current = 0;
-    let mut instr_ptr = 0;
+/// The search only supports a single loop over A which shifts it by a literal once per iteration,
+/// and whose outputs only depend on A (B and C are overwritten before being read).
+fn analyse_program(program: &[Instruction]) -> Result<ProgramShape, SolverError> {
+    if !program.len().is_multiple_of(2) {
+        return Err(MachineError::MissingOperand {
+            instr_ptr: program.len() - 1,
+        }
+        .into());
+    }
 
-    while let Some(&instr) = instructions.get(instr_ptr) {
-        match instr {
-            Instruction::Adv => {
-                registers.0 >>= combo_op(&registers, instructions[instr_ptr + 1]);
-                instr_ptr += 2;
-            }
-            Instruction::Bxl => {
-                registers.1 ^= u64::from(instructions[instr_ptr + 1]);
-                instr_ptr += 2;
-            }
-            Instruction::Bst => {
-                registers.1 = combo_op(&registers, instructions[instr_ptr + 1]) & 0b111;
-                instr_ptr += 2;
-            }
-            Instruction::Jnz => {
-                if registers.0 == 0 {
-                    instr_ptr += 2;
-                } else {
-                    instr_ptr = u64::from(instructions[instr_ptr + 1]) as usize;
-                }
+    let last_instr_ptr = program.len().saturating_sub(2);
+    if program.get(last_instr_ptr..) != Some(&[Instruction::Jnz, Instruction::Adv]) {
+        return Err(SolverError::MissingFinalJump);
+    }
+
+    let mut shifts = vec![];
+    let mut outputs_per_iteration = 0;
+    let (mut b_written, mut c_written) = (false, false);
+    for (instr_ptr, chunk) in program.chunks(2).enumerate() {
+        let instr_ptr = instr_ptr * 2;
+        let (instruction, operand) = (chunk[0], chunk[1]);
+
+        let combo_read = match u8::from(operand) {
+            5 => Some('B'),
+            6 => Some('C'),
+            7 => return Err(MachineError::InvalidComboOperand { instr_ptr }.into()),
+            _ => None,
+        };
+        let reads = match instruction {
+            Instruction::Adv
+            | Instruction::Bst
+            | Instruction::Out
+            | Instruction::Bdv
+            | Instruction::Cdv => vec![combo_read],
+            Instruction::Bxl => vec![Some('B')],
+            Instruction::Bxc => vec![Some('B'), Some('C')],
+            Instruction::Jnz => vec![],
+        };
+        for register in reads.into_iter().flatten() {
+            let written = if register == 'B' {
+                b_written
+            } else {
+                c_written
+            };
+            if !written {
+                return Err(SolverError::ReadBeforeWrite {
+                    instr_ptr,
+                    register,
+                });
             }
-            Instruction::Bxc => {
-                registers.1 ^= registers.2;
-                instr_ptr += 2;
+        }
+
+        match instruction {
+            Instruction::Adv => shifts.push((instr_ptr, operand)),
+            Instruction::Bst | Instruction::Bdv => b_written = true,
+            Instruction::Cdv => c_written = true,
+            Instruction::Out => outputs_per_iteration += 1,
+            Instruction::Jnz if instr_ptr != last_instr_ptr => {
+                return Err(SolverError::UnexpectedJump { instr_ptr })
             }
-            Instruction::Out => {
-                // result.push((combo_op(&registers, instructions[instr_ptr + 1]) & 0b111) as u8);
-                let num = (combo_op(&registers, instructions[instr_ptr + 1]) & 0b111) as u8;
-                if num != instructions[current].into() {
-                    return false;
-                }
+            Instruction::Bxl | Instruction::Bxc | Instruction::Jnz => (),
+        }
+    }
 
-                current += 1;
-                if current >= to_check {
-                    return true;
-                }
+    let &[(instr_ptr, operand)] = shifts.as_slice() else {
+        return Err(SolverError::ShiftCount {
+            count: shifts.len(),
+        });
+    };
 
-                instr_ptr += 2;
-            }
-            Instruction::Bdv => {
-                registers.1 = registers.0 >> combo_op(&registers, instructions[instr_ptr + 1]);
-                instr_ptr += 2;
-            }
-            Instruction::Cdv => {
-                registers.2 = registers.0 >> combo_op(&registers, instructions[instr_ptr + 1]);
-                instr_ptr += 2;
-            }
+    let shift = u8::from(operand) as u32;
+    if !(1..=3).contains(&shift) {
+        return Err(SolverError::NonLiteralShift { instr_ptr });
+    }
+
+    if outputs_per_iteration == 0 {
+        return Err(SolverError::NoOutput);
+    }
+
+    Ok(ProgramShape {
+        shift,
+        outputs_per_iteration,
+    })
+}
+
+/// Lowest initial value of register A for which `program` outputs exactly `target`, with B and C
+/// starting at 0.
+///
+/// Fails with [`SolverError::NoSolution`] if no value of A produces `target`, and with the other
+/// variants if `program` does not have the shape the search relies on, see `analyse_program`.
+pub fn find_register_a(program: &[Instruction], target: &[u8]) -> Result<u64, SolverError> {
+    let shape = analyse_program(program)?;
+    if target.is_empty() || !target.len().is_multiple_of(shape.outputs_per_iteration) {
+        return Err(SolverError::TargetLength {
+            target_len: target.len(),
+            outputs_per_iteration: shape.outputs_per_iteration,
+        });
+    }
+
+    let iterations = target.len() / shape.outputs_per_iteration;
+    let bits = iterations * shape.shift as usize;
+    if bits > u64::BITS as usize {
+        return Err(SolverError::Overflow { bits });
+    }
+
+    let step_limit = (iterations + 1) * program.len();
+    search_register_a(program, target, shape, step_limit, 0, iterations)?
+        .ok_or(SolverError::NoSolution)
+}
+
+/// Fixes the bits of A from the last loop iteration down to the first: the iterations from
+/// `iteration` onwards only see `A >> (shift * iteration)`, so their outputs can be checked
+/// before the lower bits are chosen.
+fn search_register_a(
+    program: &[Instruction],
+    target: &[u8],
+    shape: ProgramShape,
+    step_limit: usize,
+    upper_bits: u64,
+    iteration: usize,
+) -> Result<Option<u64>, SolverError> {
+    if iteration == 0 {
+        return Ok(Some(upper_bits));
+    }
+
+    let iteration = iteration - 1;
+    let expected = &target[iteration * shape.outputs_per_iteration..];
+    for low_bits in 0..(1 << shape.shift) {
+        let reg_a = (upper_bits << shape.shift) | low_bits;
+        let mut machine = Machine::new(program, (reg_a, 0, 0));
+        if machine.run(Some(step_limit))? != expected {
+            continue;
         }
+
+        if let Some(reg_a) =
+            search_register_a(program, target, shape, step_limit, reg_a, iteration)?
+        {
+            return Ok(Some(reg_a));
+        }
+    }
+
+    Ok(None)
+}
+
+pub struct Day17;
+
+impl Solution for Day17 {
+    const DAY: u8 = 17;
+
+    type Input<'i> = (Vec<Instruction>, Registers);
+    type Part1 = Result<String, MachineError>;
+    type Part2 = Result<u64, SolverError>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
     }
 
-    // not enough outputs...
-    false
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse_line(line: &str) -> Vec<Instruction> {
+        parse_program(line, line).unwrap()
+    }
+
     const PART1_EXAMPLE: &str = "Register A: 729
 Register B: 0
 Register C: 0
@@ -280,7 +644,10 @@ Program: 0,1,5,4,3,0";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(PART1_EXAMPLE)), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(
+            part1(&parse(PART1_EXAMPLE).unwrap()).unwrap(),
+            "4,6,3,5,6,3,5,2,1,0"
+        );
     }
 
     const PART2_EXAMPLE: &str = "Register A: 2024
@@ -291,6 +658,139 @@ Program: 0,3,5,4,3,0";
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(&parse(PART2_EXAMPLE)), 117440);
+        assert_eq!(part2(&parse(PART2_EXAMPLE).unwrap()), Ok(117440));
+    }
+
+    #[test]
+    fn find_register_a_for_targets() {
+        let outputs = |program: &[Instruction], reg_a| {
+            Machine::new(program, (reg_a, 0, 0))
+                .run(None)
+                .unwrap()
+                .to_vec()
+        };
+
+        // typical input shape, where C depends on the upper bits of A
+        let program = parse_line("Program: 2,4,1,1,7,5,1,5,4,0,0,3,5,5,3,0");
+        let target = program.iter().map(|&instr| u8::from(instr)).collect_vec();
+        let reg_a = find_register_a(&program, &target).unwrap();
+        assert_eq!(outputs(&program, reg_a), target);
+
+        // two bits consumed per loop and two outputs per loop
+        let program = parse_line("Program: 2,4,5,5,0,2,1,3,5,5,3,0");
+        let reg_a = find_register_a(&program, &[5, 6, 3, 0]).unwrap();
+        assert_eq!(reg_a, 0b1101);
+        assert_eq!(outputs(&program, reg_a), [5, 6, 3, 0]);
+        assert_eq!(
+            find_register_a(&program, &[1, 2, 3]),
+            Err(SolverError::TargetLength {
+                target_len: 3,
+                outputs_per_iteration: 2
+            })
+        );
+        assert_eq!(
+            // the bits read by both loops overlap and cannot agree
+            find_register_a(&program, &[1, 2, 3, 0]),
+            Err(SolverError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn unsupported_shapes() {
+        let shape = |line| analyse_program(&parse_line(line));
+        assert_eq!(
+            shape("Program: 0,3,5,4,3,0"),
+            Ok(ProgramShape {
+                shift: 3,
+                outputs_per_iteration: 1
+            })
+        );
+        assert_eq!(
+            shape("Program: 0,3,5,4"),
+            Err(SolverError::MissingFinalJump)
+        );
+        assert_eq!(
+            shape("Program: 0,3,3,0,5,4,3,0"),
+            Err(SolverError::UnexpectedJump { instr_ptr: 2 })
+        );
+        assert_eq!(
+            shape("Program: 0,4,5,4,3,0"),
+            Err(SolverError::NonLiteralShift { instr_ptr: 0 })
+        );
+        assert_eq!(
+            shape("Program: 0,1,0,1,5,4,3,0"),
+            Err(SolverError::ShiftCount { count: 2 })
+        );
+        assert_eq!(
+            shape("Program: 1,3,0,3,5,5,3,0"),
+            Err(SolverError::ReadBeforeWrite {
+                instr_ptr: 0,
+                register: 'B'
+            })
+        );
+        assert_eq!(shape("Program: 0,3,3,0"), Err(SolverError::NoOutput));
+    }
+
+    #[test]
+    fn disassemble_example() {
+        let (program, _) = parse(PART2_EXAMPLE).unwrap();
+        assert_eq!(
+            disassemble(&program),
+            "  0: A = A >> 3\n  2: out A % 8\n  4: if A != 0 jump 0\n"
+        );
+    }
+
+    #[test]
+    fn step_limit_and_errors() {
+        let (program, registers) = parse(PART1_EXAMPLE).unwrap();
+        let mut machine = Machine::new(&program, registers);
+        assert_eq!(
+            machine.run(Some(5)),
+            Err(MachineError::StepLimitReached { steps: 5 })
+        );
+
+        let mut traced = 0;
+        let mut machine = Machine::new(&program, registers);
+        machine.run_traced(None, |_| traced += 1).unwrap();
+        assert_eq!(traced, machine.steps);
+
+        let program = parse_line("Program: 2,7");
+        let mut machine = Machine::new(&program, (0, 0, 0));
+        assert_eq!(
+            machine.step(),
+            Err(MachineError::InvalidComboOperand { instr_ptr: 0 })
+        );
+
+        // shifting by a register larger than 63 empties A instead of overflowing
+        let (program, registers) =
+            parse("Register A: 12345\nRegister B: 100\nRegister C: 64\n\nProgram: 7,6,0,5,5,4")
+                .unwrap();
+        let mut machine = Machine::new(&program, registers);
+        assert_eq!(machine.run(None), Ok([0].as_slice()));
+        assert_eq!(machine.registers(), (0, 100, 0));
+
+        let program = parse_line("Program: 1");
+        let mut machine = Machine::new(&program, (0, 0, 0));
+        assert_eq!(
+            machine.step(),
+            Err(MachineError::MissingOperand { instr_ptr: 0 })
+        );
+    }
+
+    #[test]
+    fn single_steps() {
+        let (program, registers) =
+            Day17::parse("Register A: 10\nRegister B: 0\nRegister C: 0\n\nProgram: 5,0,5,1,5,4")
+                .unwrap();
+        let mut machine = Machine::new(&program, registers);
+        for (value, instr_ptr) in [(0, 2), (1, 4), (2, 6)] {
+            assert_eq!(machine.step(), Ok(Step::Output(value)));
+            assert_eq!(machine.instr_ptr(), instr_ptr);
+        }
+
+        assert!(machine.is_halted());
+        assert_eq!(machine.step(), Ok(Step::Halted));
+        assert_eq!(machine.output(), [0, 1, 2]);
+        assert_eq!(machine.registers(), (10, 0, 0));
     }
 }
@@ -1,113 +1,101 @@
 use core::fmt;
-use std::{error::Error, str::FromStr};
 
-use fnv::FnvHashSet;
-use itertools::Itertools;
+use fnv::FnvHashMap;
 use ndarray::Array2;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Position(isize, isize);
+use crate::{
+    grid::{Position, ALL_NEIGHBORS, DIRECT_NEIGHBORS},
+    parse::{Parameters, ParseError},
+    pathfinding::{self, SearchSpace},
+    solution::Solution,
+};
 
-const DIRECT_NEIGHBORS: [Position; 4] = [
-    Position(1, 0),
-    Position(0, 1),
-    Position(-1, 0),
-    Position(0, -1),
-];
-
-impl Position {
-    fn into_usize_tuple(self) -> (usize, usize) {
-        if self.0 < 0 || self.1 < 0 {
-            panic!("Cannot convert {self} to usize pair");
-        }
-
-        (self.0 as usize, self.1 as usize)
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Tile {
+    #[default]
+    Walkable,
+    /// Walkable, but entering it costs the given amount instead of 1.
+    Terrain(u64),
+    Corrupted,
 }
 
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if f.alternate() {
-            write!(f, "({}, {})", self.0, self.1)
-        } else {
-            write!(f, "{},{}", self.0, self.1)
+impl Tile {
+    /// Cost of entering the tile, `None` if it cannot be entered.
+    fn cost(self) -> Option<u64> {
+        match self {
+            Tile::Walkable => Some(1),
+            Tile::Terrain(cost) => Some(cost),
+            Tile::Corrupted => None,
         }
     }
 }
 
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
-
-impl std::ops::Mul<isize> for Position {
-    type Output = Position;
-
-    fn mul(self, rhs: isize) -> Self::Output {
-        Self(self.0 * rhs, self.1 * rhs)
-    }
-}
+/// Step weights of [`Movement::Octile`], a diagonal step costing about √2 straight ones.
+const OCTILE_STRAIGHT: u64 = 100;
+const OCTILE_DIAGONAL: u64 = 141;
 
-impl std::ops::MulAssign<isize> for Position {
-    fn mul_assign(&mut self, rhs: isize) {
-        *self = *self * rhs;
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Movement {
+    /// Up, down, left and right only.
+    #[default]
+    Orthogonal,
+    /// Diagonal steps too, at the same cost as straight ones.
+    Diagonal,
+    /// Diagonal steps too, costs being in hundredths of a straight step.
+    Octile,
 }
 
-impl FromStr for Position {
-    type Err = Box<dyn Error>;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let Some((x, y)) = s.split_once(',') else {
-            return Err(format!("{s:?} does not contain a ','").into());
+impl Movement {
+    /// Steps with the weight their tile cost is multiplied by.
+    fn steps(self) -> impl Iterator<Item = (Position, u64)> {
+        let (neighbours, straight, diagonal): (&[Position], _, _) = match self {
+            Movement::Orthogonal => (&DIRECT_NEIGHBORS, 1, 1),
+            Movement::Diagonal => (&ALL_NEIGHBORS, 1, 1),
+            Movement::Octile => (&ALL_NEIGHBORS, OCTILE_STRAIGHT, OCTILE_DIAGONAL),
         };
 
-        Ok(Self(x.trim().parse()?, y.trim().parse()?))
+        neighbours.iter().map(move |&step| {
+            let weight = if step.0 != 0 && step.1 != 0 {
+                diagonal
+            } else {
+                straight
+            };
+            (step, weight)
+        })
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
-enum Tile {
-    #[default]
-    Walkable,
-    Corrupted,
+    /// Weight of the cheapest way from `from` to `to` on an empty grid.
+    fn distance(self, from: Position, to: Position) -> u64 {
+        match self {
+            Movement::Orthogonal => from.manhattan_distance(&to),
+            Movement::Diagonal => from.chebyshev_distance(&to),
+            Movement::Octile => {
+                let diagonal = from.0.abs_diff(to.0).min(from.1.abs_diff(to.1)) as u64;
+                let straight = from.chebyshev_distance(&to) - diagonal;
+                straight * OCTILE_STRAIGHT + diagonal * OCTILE_DIAGONAL
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Grid {
+pub struct Grid {
     map: Array2<Tile>,
+    movement: Movement,
 }
 
 impl Grid {
-    fn new(size: Position) -> Self {
+    pub fn new(size: Position) -> Self {
         Grid {
             map: Array2::default((size.0 as usize, size.1 as usize)),
+            movement: Movement::default(),
         }
     }
 
+    pub fn with_movement(self, movement: Movement) -> Self {
+        Grid { movement, ..self }
+    }
+
     fn grid_size(&self) -> Position {
         let mut axes = self.map.axes();
         let x = axes.next().expect("X Axis missing").len as isize;
@@ -119,6 +107,15 @@ impl Grid {
         self[pos] == Tile::Corrupted
     }
 
+    /// Cheapest cost of entering a tile, 1 on a grid without terrain.
+    fn min_cost(&self) -> u64 {
+        self.map
+            .iter()
+            .filter_map(|tile| tile.cost())
+            .min()
+            .unwrap_or(1)
+    }
+
     fn shape(&self) -> [usize; 2] {
         let grid_size = self.grid_size();
         [grid_size.0 as usize, grid_size.1 as usize]
@@ -148,11 +145,13 @@ impl std::ops::IndexMut<Position> for Grid {
 impl fmt::Display for Grid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let shape = self.shape();
-        for col_idx in 0..shape[0] {
-            for row_idx in 0..shape[1] {
-                match self.map[(row_idx, col_idx)] {
+        for y in 0..shape[1] {
+            for x in 0..shape[0] {
+                match self.map[(x, y)] {
                     Tile::Corrupted => write!(f, "#")?,
                     Tile::Walkable => write!(f, " ")?,
+                    Tile::Terrain(cost @ 0..=9) => write!(f, "{cost}")?,
+                    Tile::Terrain(_) => write!(f, "+")?,
                 }
             }
 
@@ -163,185 +162,298 @@ impl fmt::Display for Grid {
     }
 }
 
-mod a_star {
-    use std::{
-        cmp::Reverse,
-        collections::{BinaryHeap, VecDeque},
-    };
-
-    use super::*;
+struct MemorySpace<'g> {
+    grid: &'g Grid,
+    target: Position,
+    min_cost: u64,
+}
 
-    #[derive(Debug, Clone, Copy)]
-    struct AStarNode {
-        g: usize,
-        h: usize,
-        parent: Position,
+impl SearchSpace for MemorySpace<'_> {
+    type State = Position;
+
+    fn neighbours(&self, position: &Position) -> impl IntoIterator<Item = (Position, u64)> {
+        self.grid
+            .movement
+            .steps()
+            .filter_map(move |(step, weight)| {
+                let neighbour = *position + step;
+                let cost = self.grid[neighbour].cost()?;
+                Some((neighbour, cost * weight))
+            })
     }
 
-    impl AStarNode {
-        const fn f(&self) -> usize {
-            self.g.saturating_add(self.h)
-        }
+    fn is_goal(&self, position: &Position) -> bool {
+        *position == self.target
     }
 
-    impl Default for AStarNode {
-        fn default() -> Self {
-            Self {
-                g: usize::MAX,
-                h: usize::MAX,
-                parent: Position(-1, -1),
-            }
-        }
+    fn heuristic(&self, position: &Position) -> u64 {
+        self.grid.movement.distance(*position, self.target) * self.min_cost
     }
+}
 
-    fn a_star_heuristic(from: Position, to: Position) -> usize {
-        from.0.abs_diff(to.0) + from.1.abs_diff(to.1)
+/// Cheapest path from `start_pos` to `target_pos` with its total cost, the cost of a step being
+/// the cost of the tile entered weighted by the [`Movement`] of the grid.
+pub fn shortest_path(
+    grid: &Grid,
+    start_pos: Position,
+    target_pos: Position,
+) -> Option<(Vec<Position>, u64)> {
+    if grid.is_blocked(target_pos) || grid.is_blocked(start_pos) {
+        return None;
     }
 
-    fn trace_route(nodes: &Array2<AStarNode>, mut end_pos: Position) -> Vec<Position> {
-        let mut result = VecDeque::new();
-        result.push_front(end_pos);
+    let space = MemorySpace {
+        grid,
+        target: target_pos,
+        min_cost: grid.min_cost(),
+    };
 
-        while let Some(node) = trace_route_exit_condition(nodes, end_pos) {
-            end_pos = node.parent;
-            result.push_front(end_pos);
-        }
+    pathfinding::a_star(&space, start_pos).shortest_path()
+}
 
-        result.into()
+fn part1_with_grid_size(
+    falling_bytes: &[Position],
+    grid_size: Position,
+    falling_bytes_to_take: usize,
+) -> Option<usize> {
+    let target = grid_size - Position(1, 1);
+    let mut grid = Grid::new(grid_size);
+    for &byte in falling_bytes.iter().take(falling_bytes_to_take) {
+        grid[byte] = Tile::Corrupted;
     }
 
-    fn trace_route_exit_condition(
-        nodes: &Array2<AStarNode>,
-        end_pos: Position,
-    ) -> Option<AStarNode> {
-        let node = nodes[end_pos.into_usize_tuple()];
-        (node.parent != end_pos).then_some(node)
-    }
+    shortest_path(&grid, Position(0, 0), target).map(|(_, cost)| cost as usize)
+}
 
-    pub fn a_star(grid: &Grid, start_pos: Position, target_pos: Position) -> Option<Vec<Position>> {
-        if grid.is_blocked(target_pos) || grid.is_blocked(start_pos) {
-            return None;
-        }
+/// Disjoint sets over the cells of the grid, indexed like `Array2` in standard layout.
+struct DisjointSet {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+}
 
-        let mut closed_list: Array2<bool> = Array2::from_elem(grid.shape(), false);
-        let mut nodes: Array2<AStarNode> = Array2::default(grid.shape());
-        for row in 0..grid.shape()[0] {
-            for col in 0..grid.shape()[1] {
-                let node = &mut nodes[(row, col)];
-                node.parent = Position(row as isize, col as isize);
-                node.h = a_star_heuristic(node.parent, target_pos);
-            }
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        DisjointSet {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
         }
+    }
 
-        nodes[start_pos.into_usize_tuple()] = AStarNode {
-            g: 0,
-            h: 0,
-            parent: start_pos,
-        };
-
-        let mut open_list =
-            BinaryHeap::<(Reverse<usize>, Position)>::from_iter([(Reverse(0), start_pos)]);
-        while let Some((_, position)) = open_list.pop() {
-            let closed = &mut closed_list[position.into_usize_tuple()];
-            if *closed {
-                continue;
-            }
-
-            *closed = true;
-
-            for neighbour in DIRECT_NEIGHBORS.map(|neighbour| neighbour + position) {
-                if grid.is_blocked(neighbour) || closed_list[neighbour.into_usize_tuple()] {
-                    continue;
-                }
-
-                if target_pos == neighbour {
-                    nodes[neighbour.into_usize_tuple()].parent = position;
-                    return Some(trace_route(&nodes, target_pos));
-                }
+    fn find(&mut self, mut element: usize) -> usize {
+        while self.parents[element] != element {
+            self.parents[element] = self.parents[self.parents[element]];
+            element = self.parents[element];
+        }
 
-                let mut node_clone = nodes[neighbour.into_usize_tuple()];
-                node_clone.g = nodes[position.into_usize_tuple()].g + 1;
-                if nodes[neighbour.into_usize_tuple()].f() <= node_clone.f() {
-                    continue;
-                }
+        element
+    }
 
-                node_clone.parent = position;
-                nodes[neighbour.into_usize_tuple()] = node_clone;
-                open_list.push((Reverse(node_clone.f()), neighbour));
-            }
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
         }
 
-        None
+        let (small, large) = if self.ranks[a] < self.ranks[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[small] = large;
+        if self.ranks[small] == self.ranks[large] {
+            self.ranks[large] += 1;
+        }
     }
 }
 
-fn part1_with_grid_size(
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockingByte {
+    /// Index of the byte in the order they fall.
+    pub index: usize,
+    pub position: Position,
+}
+
+/// Finds the first byte after which the exit cannot be reached anymore, or `None` if it still
+/// can once all bytes have fallen.
+///
+/// Works backwards from the grid with every byte fallen: bytes are removed in reverse order and
+/// the freed cells joined with their free neighbours until start and exit end up connected.
+pub fn first_blocking_byte(
     falling_bytes: &[Position],
     grid_size: Position,
-    falling_bytes_to_take: usize,
-) -> usize {
-    let target = grid_size - Position(1, 1);
+) -> Option<BlockingByte> {
     let mut grid = Grid::new(grid_size);
-    for &byte in falling_bytes.iter().take(falling_bytes_to_take) {
+    for &byte in falling_bytes {
         grid[byte] = Tile::Corrupted;
     }
 
-    // println!("{grid}");
-    a_star::a_star(&grid, Position(0, 0), target)
-        .expect("Could not find a path")
-        .len()
-        - 1
-}
+    let height = grid_size.1 as usize;
+    let cell = |position: Position| {
+        let (x, y) = position.into_usize_tuple();
+        x * height + y
+    };
+    let mut sets = DisjointSet::new(grid.map.len());
+    let free = |grid: &Grid, sets: &mut DisjointSet, position: Position| {
+        for neighbour in DIRECT_NEIGHBORS.map(|neighbour| neighbour + position) {
+            if !grid.is_blocked(neighbour) {
+                sets.union(cell(position), cell(neighbour));
+            }
+        }
+    };
+    for x in 0..grid_size.0 {
+        for y in 0..grid_size.1 {
+            if !grid.is_blocked(Position(x, y)) {
+                free(&grid, &mut sets, Position(x, y));
+            }
+        }
+    }
 
-fn part2_with_grid_size(
-    falling_bytes: &[Position],
-    grid_size: Position,
-    fallen_bytes: usize,
-) -> Position {
-    let target = grid_size - Position(1, 1);
-    let mut grid = Grid::new(grid_size);
-    for &byte in falling_bytes.iter().take(fallen_bytes) {
-        grid[byte] = Tile::Corrupted;
+    let (start, target) = (Position(0, 0), grid_size - Position(1, 1));
+    let connected = |grid: &Grid, sets: &mut DisjointSet| {
+        !grid.is_blocked(start)
+            && !grid.is_blocked(target)
+            && sets.find(cell(start)) == sets.find(cell(target))
+    };
+    if connected(&grid, &mut sets) {
+        return None;
     }
 
-    let mut path = a_star::a_star(&grid, Position(0, 0), target)
-        .expect("First path could not be resolved")
-        .into_iter()
-        .collect::<FnvHashSet<_>>();
+    // a byte falling twice on the same cell only frees it once its first fall is undone
+    let mut first_falls = FnvHashMap::default();
+    for (index, &byte) in falling_bytes.iter().enumerate() {
+        first_falls.entry(byte).or_insert(index);
+    }
 
-    for i in fallen_bytes..falling_bytes.len() {
-        grid[falling_bytes[i]] = Tile::Corrupted;
-        if !path.contains(&falling_bytes[i]) {
+    for (index, &byte) in falling_bytes.iter().enumerate().rev() {
+        if first_falls[&byte] != index {
             continue;
         }
 
-        path = match a_star::a_star(&grid, Position(0, 0), target) {
-            Some(p) => p.into_iter().collect(),
-            None => return falling_bytes[i],
-        };
+        grid[byte] = Tile::Walkable;
+        free(&grid, &mut sets, byte);
+        if connected(&grid, &mut sets) {
+            return Some(BlockingByte {
+                index,
+                position: byte,
+            });
+        }
     }
 
-    unreachable!("Falling bytes did not completely block the path");
+    None
+}
+
+/// Size of the memory space and number of bytes fallen in part 1 of the puzzle.
+const GRID_SIZE: Position = Position(71, 71);
+const FALLEN_BYTES: usize = 1024;
+/// The same for the example, used when all bytes fall in it.
+const EXAMPLE_GRID_SIZE: Position = Position(7, 7);
+const EXAMPLE_FALLEN_BYTES: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FallingBytes {
+    bytes: Vec<Position>,
+    grid_size: Position,
+    fallen: usize,
+}
+
+impl FallingBytes {
+    /// The first byte cutting the exit off, its index counting all the bytes of the input from 0
+    /// and not only the ones after the `fallen` of part 1.
+    pub fn first_blocking_byte(&self) -> Option<BlockingByte> {
+        first_blocking_byte(&self.bytes, self.grid_size)
+    }
 }
 
+/// Bytes, one per line, after an optional `# size=<width>x<height> bytes=<fallen>` line.
 #[aoc_generator(day18)]
-fn parse(input: &str) -> Result<Vec<Position>, Box<dyn Error>> {
-    input.lines().map(|line| line.parse()).try_collect()
+fn parse(input: &str) -> Result<FallingBytes, ParseError> {
+    let (parameters, bytes) = Parameters::split(18, input, &["size", "bytes"])?;
+    let bytes = bytes
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.parse::<Position>()
+                .map_err(|error| ParseError::at(18, input, line, error))
+                .map(|byte| (line, byte))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let fits = |grid_size: Position, (_, byte): &(&str, Position)| {
+        (0..grid_size.0).contains(&byte.0) && (0..grid_size.1).contains(&byte.1)
+    };
+    let example = bytes.iter().all(|byte| fits(EXAMPLE_GRID_SIZE, byte));
+    let grid_size = match parameters.dimension("size")? {
+        Some(grid_size) => grid_size,
+        None if example => EXAMPLE_GRID_SIZE,
+        None => GRID_SIZE,
+    };
+    let fallen = match parameters.get("bytes")? {
+        Some(fallen) => fallen,
+        None if example => EXAMPLE_FALLEN_BYTES,
+        None => FALLEN_BYTES,
+    };
+    if let Some((line, _)) = bytes.iter().find(|byte| !fits(grid_size, byte)) {
+        return Err(ParseError::at(
+            18,
+            input,
+            line,
+            format!(
+                "Byte falls outside of the {}x{} grid",
+                grid_size.0, grid_size.1
+            ),
+        ));
+    }
+
+    Ok(FallingBytes {
+        bytes: bytes.into_iter().map(|(_, byte)| byte).collect(),
+        grid_size,
+        fallen,
+    })
 }
 
 #[aoc(day18, part1)]
-fn part1(falling_bytes: &[Position]) -> usize {
-    part1_with_grid_size(falling_bytes, Position(71, 71), 1024)
+fn part1(falling_bytes: &FallingBytes) -> Option<usize> {
+    part1_with_grid_size(
+        &falling_bytes.bytes,
+        falling_bytes.grid_size,
+        falling_bytes.fallen,
+    )
 }
 
 #[aoc(day18, part2)]
-fn part2(falling_bytes: &[Position]) -> Position {
-    part2_with_grid_size(falling_bytes, Position(71, 71), 1024)
+fn part2(falling_bytes: &FallingBytes) -> Option<String> {
+    let BlockingByte { position, .. } = falling_bytes.first_blocking_byte()?;
+    Some(format!("{},{}", position.0, position.1))
+}
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: u8 = 18;
+
+    type Input<'i> = FallingBytes;
+    type Part1 = Option<usize>;
+    type Part2 = Option<String>;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
     use super::*;
+    use crate::testing::Random;
 
     const EXAMPLE: &str = "5,4
 4,2
@@ -371,17 +483,165 @@ mod tests {
 
     #[test]
     fn part1_example() {
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), Some(22));
+        // enough bytes to cut off the exit
         assert_eq!(
-            part1_with_grid_size(&parse(EXAMPLE).unwrap(), Position(7, 7), 12),
-            22
+            part1(&parse(&format!("# bytes=21\n{EXAMPLE}")).unwrap()),
+            None
         );
     }
 
     #[test]
     fn part2_example() {
+        let falling_bytes = parse(EXAMPLE).unwrap();
+        assert_eq!(part2(&falling_bytes).as_deref(), Some("6,1"));
+        assert_eq!(falling_bytes.first_blocking_byte().unwrap().index, 20);
+
+        // the path is never cut in a larger memory space
+        assert_eq!(
+            part2(&parse(&format!("# size=9x9\n{EXAMPLE}")).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn weighted_paths() {
+        let falling_bytes = parse(EXAMPLE).unwrap();
+        let mut grid = Grid::new(EXAMPLE_GRID_SIZE);
+        for &byte in &falling_bytes.bytes[..EXAMPLE_FALLEN_BYTES] {
+            grid[byte] = Tile::Corrupted;
+        }
+        let target = EXAMPLE_GRID_SIZE - Position(1, 1);
+        let cost = |grid: &Grid| shortest_path(grid, Position(0, 0), target).unwrap().1;
+
+        assert_eq!(cost(&grid), 22);
+        let (path, _) = shortest_path(&grid, Position(0, 0), target).unwrap();
+        assert_eq!(path.len(), 23);
+
+        // the cheapest detour around the swamp in the top left corner is one step longer
+        let mut swamp = grid.clone();
+        swamp[Position(1, 0)] = Tile::Terrain(5);
+        swamp[Position(0, 1)] = Tile::Terrain(5);
+        assert_eq!(cost(&swamp), 22 + 4);
+        // free terrain everywhere but on the exit, the heuristic has to stay admissible
+        let mut road = grid.clone();
+        road.map
+            .iter_mut()
+            .filter(|tile| **tile == Tile::Walkable)
+            .for_each(|tile| *tile = Tile::Terrain(0));
+        road[target] = Tile::Walkable;
+        assert_eq!(cost(&road), 1);
+
+        let mut grid = grid.with_movement(Movement::Diagonal);
+        assert_eq!(cost(&grid), 7);
+        let (path, _) = shortest_path(&grid, Position(0, 0), target).unwrap();
+        assert!(path
+            .iter()
+            .tuple_windows()
+            .all(|(a, b)| a.chebyshev_distance(b) == 1 && !grid.is_blocked(*b)));
+
+        grid = grid.with_movement(Movement::Octile);
+        let (path, octile_cost) = shortest_path(&grid, Position(0, 0), target).unwrap();
+        let expected = path
+            .iter()
+            .tuple_windows()
+            .map(|(a, b)| match a.manhattan_distance(b) {
+                1 => OCTILE_STRAIGHT,
+                _ => OCTILE_DIAGONAL,
+            })
+            .sum::<u64>();
+        assert_eq!(octile_cost, expected);
+        assert_eq!(octile_cost, 5 * OCTILE_DIAGONAL + 2 * OCTILE_STRAIGHT);
+
+        let empty = Grid::new(Position(4, 3)).with_movement(Movement::Octile);
+        assert_eq!(
+            shortest_path(&empty, Position(0, 0), Position(3, 2))
+                .unwrap()
+                .1,
+            OCTILE_STRAIGHT + 2 * OCTILE_DIAGONAL
+        );
+    }
+
+    #[test]
+    fn blocking_byte() {
+        let falling_bytes = parse(EXAMPLE).unwrap();
+        assert_eq!(
+            first_blocking_byte(&falling_bytes.bytes, EXAMPLE_GRID_SIZE),
+            Some(BlockingByte {
+                index: 20,
+                position: Position(6, 1)
+            })
+        );
+        assert_eq!(
+            first_blocking_byte(&falling_bytes.bytes[..20], EXAMPLE_GRID_SIZE),
+            None
+        );
+        // the byte blocking the start again does not count, the first fall did
+        let bytes = [
+            Position(2, 2),
+            Position(0, 0),
+            Position(1, 1),
+            Position(0, 0),
+        ];
+        assert_eq!(
+            first_blocking_byte(&bytes, Position(4, 4)),
+            Some(BlockingByte {
+                index: 1,
+                position: Position(0, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn blocking_byte_matches_path_search() {
+        let grid_size = Position(13, 11);
+        for seed in 0..20 {
+            let mut random = Random::new(seed);
+            let bytes = (0..120)
+                .map(|_| {
+                    let cell = random.below((grid_size.0 * grid_size.1) as u64) as isize;
+                    Position(cell / grid_size.1, cell % grid_size.1)
+                })
+                .collect::<Vec<_>>();
+
+            let mut grid = Grid::new(grid_size);
+            let expected = bytes.iter().position(|&byte| {
+                grid[byte] = Tile::Corrupted;
+                shortest_path(&grid, Position(0, 0), grid_size - Position(1, 1)).is_none()
+            });
+            assert_eq!(
+                first_blocking_byte(&bytes, grid_size).map(|byte| byte.index),
+                expected,
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn grid_size() {
+        let falling_bytes = parse(EXAMPLE).unwrap();
+        assert_eq!(falling_bytes.grid_size, EXAMPLE_GRID_SIZE);
+        assert_eq!(falling_bytes.fallen, EXAMPLE_FALLEN_BYTES);
+
+        let falling_bytes = parse("2,0\n70,3").unwrap();
+        assert_eq!(falling_bytes.grid_size, GRID_SIZE);
+        assert_eq!(falling_bytes.fallen, FALLEN_BYTES);
+
+        let falling_bytes = parse(&format!("# bytes=20 size=9x8\n{EXAMPLE}")).unwrap();
+        assert_eq!(falling_bytes.grid_size, Position(9, 8));
+        assert_eq!(falling_bytes.fallen, 20);
+        let falling_bytes = parse(&format!("# size=9x8 bytes=0\n{EXAMPLE}")).unwrap();
+        assert_eq!(part1(&falling_bytes), Some(8 + 7));
+
         assert_eq!(
-            part2_with_grid_size(&parse(EXAMPLE).unwrap(), Position(7, 7), 12),
-            Position(6, 1)
+            parse("# size=3x3\n1,1\n0,3").unwrap_err().to_string(),
+            "Day 18, line 3, column 1: Byte falls outside of the 3x3 grid"
         );
+        assert!(parse("# bytes=-1\n1,1").is_err());
+
+        let mut grid = Grid::new(Position(3, 2));
+        grid[Position(2, 0)] = Tile::Corrupted;
+        grid[Position(0, 1)] = Tile::Corrupted;
+        assert_eq!(grid.to_string(), "  #\n#  \n");
     }
 }
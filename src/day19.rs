@@ -3,27 +3,31 @@ use core::str;
 use itertools::Itertools;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Debug, Clone)]
-struct Input<'a> {
+pub struct Input<'a> {
     designs: Vec<&'a [u8]>,
     patterns: Vec<&'a [u8]>,
 }
 
-fn parse(input: &str) -> Input<'_> {
+fn parse(input: &str) -> Result<Input<'_>, ParseError> {
     let mut lines = input.lines();
-    let patterns = lines.next().expect("First line did not exist");
+    let patterns = lines
+        .next()
+        .ok_or_else(|| ParseError::at_end(19, input, "First line did not exist"))?;
     let patterns = patterns
         .split(',')
         .map(|pattern| pattern.trim().as_bytes())
         .collect_vec();
 
-    Input {
+    Ok(Input {
         designs: lines
             .filter(|line| !line.is_empty())
             .map(str::as_bytes)
             .collect_vec(),
         patterns,
-    }
+    })
 }
 
 fn part1_check_pattern_rec(
@@ -91,9 +95,7 @@ fn part2_check_pattern(design: &[u8], patterns: &[&[u8]]) -> u64 {
     part2_check_pattern_rec(design, patterns, 0, &mut vec![u64::MAX; design.len()])
 }
 
-#[aoc(day19, part1)]
-fn part1(input: &str) -> usize {
-    let input = parse(input);
+fn count_possible(input: &Input) -> usize {
     input
         .designs
         .par_iter()
@@ -102,9 +104,7 @@ fn part1(input: &str) -> usize {
         .count()
 }
 
-#[aoc(day19, part2)]
-fn part2(input: &str) -> u64 {
-    let input = parse(input);
+fn count_arrangements(input: &Input) -> u64 {
     input
         .designs
         .par_iter()
@@ -112,6 +112,38 @@ fn part2(input: &str) -> u64 {
         .sum()
 }
 
+#[aoc(day19, part1)]
+fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(count_possible(&parse(input)?))
+}
+
+#[aoc(day19, part2)]
+fn part2(input: &str) -> Result<u64, ParseError> {
+    Ok(count_arrangements(&parse(input)?))
+}
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: u8 = 19;
+
+    type Input<'i> = Input<'i>;
+    type Part1 = usize;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        count_possible(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        count_arrangements(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,11 +161,11 @@ bbrgwb";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(EXAMPLE), 6);
+        assert_eq!(part1(EXAMPLE), Ok(6));
     }
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(EXAMPLE), 16);
+        assert_eq!(part2(EXAMPLE), Ok(16));
     }
 }
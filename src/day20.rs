@@ -2,83 +2,12 @@ use std::collections::VecDeque;
 
 use fnv::{FnvHashMap, FnvHashSet};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Position(isize, isize);
-
-const DIRECT_NEIGHBORS: [Position; 4] = [
-    Position(1, 0),
-    Position(0, 1),
-    Position(-1, 0),
-    Position(0, -1),
-];
-
-impl Position {
-    fn manhattan_distance(&self, other: &Self) -> u64 {
-        (self.0.abs_diff(other.0) + self.1.abs_diff(other.1)) as u64
-    }
-
-    fn iter_positions_within(
-        &self,
-        max_distance: usize,
-    ) -> impl IntoIterator<Item = (Position, u64)> + '_ {
-        let max_isize = max_distance as isize;
-        ((self.0 - max_isize)..=(self.0 + max_isize))
-            .flat_map(move |x_dist| {
-                ((self.1 - max_isize)..=(self.1 + max_isize))
-                    .map(move |y_dist| Position(x_dist, y_dist))
-            })
-            .map(|position| (position, self.manhattan_distance(&position)))
-            .filter(move |(_, dist)| (*dist as usize) <= max_distance)
-    }
-}
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
-
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
-
-impl std::ops::Mul<isize> for Position {
-    type Output = Position;
-
-    fn mul(self, rhs: isize) -> Self::Output {
-        Self(self.0 * rhs, self.1 * rhs)
-    }
-}
-
-impl std::ops::MulAssign<isize> for Position {
-    fn mul_assign(&mut self, rhs: isize) {
-        *self = *self * rhs;
-    }
-}
+use crate::{
+    grid::{Grid, Position, DIRECT_NEIGHBORS},
+    parse::ParseError,
+    pathfinding::{self, SearchSpace},
+    solution::Solution,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum Tile {
@@ -87,52 +16,26 @@ enum Tile {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-struct Grid {
+pub struct RaceTrack {
     start_pos: Position,
     end_pos: Position,
-    map: Vec<Vec<Tile>>,
+    map: Grid<Tile>,
 }
 
-impl Grid {
-    fn neighbours(&self, pos: Position) -> impl IntoIterator<Item = Position> + '_ {
-        DIRECT_NEIGHBORS
-            .into_iter()
-            .map(move |delta| delta + pos)
-            .filter(|pos| self[*pos] == Tile::Walkable)
-    }
-
+impl RaceTrack {
     fn costs_to_end(&self) -> FnvHashMap<Position, u64> {
-        let mut costs = FnvHashMap::default();
-        let mut queue = VecDeque::new();
-        queue.push_back((self.end_pos, 0));
-
-        while let Some((pos, cost)) = queue.pop_front() {
-            costs.insert(pos, cost);
-            for neighbour in self.neighbours(pos) {
-                if costs.contains_key(&neighbour) {
-                    continue;
-                }
-
-                queue.push_back((neighbour, cost + 1));
-            }
-        }
-
-        costs
+        pathfinding::bfs(self, self.end_pos).costs
     }
 }
 
-impl std::ops::Index<Position> for Grid {
-    type Output = Tile;
-
-    fn index(&self, index: Position) -> &Self::Output {
-        if index.0 < 0 || index.1 < 0 {
-            return &Tile::Blocked;
-        }
+impl SearchSpace for RaceTrack {
+    type State = Position;
 
+    fn neighbours(&self, pos: &Position) -> impl IntoIterator<Item = (Position, u64)> {
         self.map
-            .get(index.0 as usize)
-            .and_then(|row| row.get(index.1 as usize))
-            .unwrap_or(&Tile::Blocked)
+            .neighbours(*pos)
+            .filter(|pos| self.map[*pos] == Tile::Walkable)
+            .map(|pos| (pos, 1))
     }
 }
 
@@ -161,7 +64,7 @@ fn cheat_deltas(cheat_duration: usize) -> Vec<Position> {
     result
 }
 
-fn count_cheats_part1<F>(grid: &Grid, mut accept_cheat: F) -> u64
+fn count_cheats_part1<F>(grid: &RaceTrack, mut accept_cheat: F) -> u64
 where
     F: FnMut(u64) -> bool,
 {
@@ -197,7 +100,7 @@ where
     F: FnMut(u64) -> bool,
 {
     let mut accepted = 0;
-    for (end, covered) in from.iter_positions_within(cheat_duration) {
+    for (end, covered) in from.positions_within(cheat_duration) {
         if let Some(end_cost) = costs.get(&end) {
             if *end_cost > from_cost {
                 continue;
@@ -213,7 +116,7 @@ where
     accepted
 }
 
-fn count_cheats_part2<F>(grid: &Grid, mut accept_cheat: F, cheat_duration: usize) -> u64
+fn count_cheats_part2<F>(grid: &RaceTrack, mut accept_cheat: F, cheat_duration: usize) -> u64
 where
     F: FnMut(u64) -> bool,
 {
@@ -233,48 +136,66 @@ where
 }
 
 #[aoc_generator(day20)]
-fn parse(input: &str) -> Grid {
+fn parse(input: &str) -> Result<RaceTrack, ParseError> {
     let mut start_pos = None;
     let mut end_pos = None;
-    let mut map = vec![];
-    for (row_idx, line) in input.lines().filter(|line| !line.is_empty()).enumerate() {
-        let mut row = vec![];
-        for (col_idx, c) in line.trim().as_bytes().iter().enumerate() {
-            match c {
-                b'#' => row.push(Tile::Blocked),
-                b'.' | b' ' => row.push(Tile::Walkable),
-                b'S' => {
-                    start_pos = Some(Position(row_idx as isize, col_idx as isize));
-                    row.push(Tile::Walkable);
-                }
-                b'E' => {
-                    end_pos = Some(Position(row_idx as isize, col_idx as isize));
-                    row.push(Tile::Walkable);
-                }
-                _ => panic!("{:?} was not any of '#', '.', ' ', 'S' or 'E'", *c as char),
-            }
+    let map = Grid::parse_with(input, |position, c| match c {
+        b'#' => Ok(Tile::Blocked),
+        b'.' | b' ' => Ok(Tile::Walkable),
+        b'S' => {
+            start_pos = Some(position);
+            Ok(Tile::Walkable)
         }
-
-        map.push(row);
-    }
-
-    Grid {
-        start_pos: start_pos.expect("Start not found"),
-        end_pos: end_pos.expect("End not found"),
+        b'E' => {
+            end_pos = Some(position);
+            Ok(Tile::Walkable)
+        }
+        _ => Err(format!(
+            "{:?} was not any of '#', '.', ' ', 'S' or 'E'",
+            c as char
+        )),
+    })
+    .map_err(|error| ParseError::from_grid(20, error))?;
+
+    Ok(RaceTrack {
+        start_pos: start_pos.ok_or_else(|| ParseError::at_end(20, input, "Start not found"))?,
+        end_pos: end_pos.ok_or_else(|| ParseError::at_end(20, input, "End not found"))?,
         map,
-    }
+    })
 }
 
 #[aoc(day20, part1)]
-fn part1(grid: &Grid) -> u64 {
+fn part1(grid: &RaceTrack) -> u64 {
     count_cheats_part1(grid, |picoseconds_saved| picoseconds_saved >= 100)
 }
 
 #[aoc(day20, part2)]
-fn part2(grid: &Grid) -> u64 {
+fn part2(grid: &RaceTrack) -> u64 {
     count_cheats_part2(grid, |picoseconds_saved| picoseconds_saved >= 100, 20)
 }
 
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: u8 = 20;
+
+    type Input<'i> = RaceTrack;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use fnv::FnvHashMap;
@@ -313,7 +234,7 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        let grid = parse(EXAMPLE);
+        let grid = parse(EXAMPLE).unwrap();
         let expected_cheats: FnvHashMap<u64, u64> = EXAMPLE1_CHEATS.into_iter().collect();
         let mut cheats_found = FnvHashMap::default();
 
@@ -351,7 +272,7 @@ mod tests {
 
     #[test]
     fn part2_example() {
-        let grid = parse(EXAMPLE);
+        let grid = parse(EXAMPLE).unwrap();
         let expected_cheats: FnvHashMap<u64, u64> = EXAMPLE2_CHEATS.into_iter().collect();
         let mut cheats_found = FnvHashMap::default();
 
@@ -1,55 +1,6 @@
 use fnv::FnvHashMap;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Position(isize, isize);
-
-impl std::fmt::Display for Position {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {})", self.0, self.1)
-    }
-}
-
-impl std::ops::Add for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Self) -> Self::Output {
-        Self(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl std::ops::AddAssign for Position {
-    fn add_assign(&mut self, rhs: Self) {
-        *self = *self + rhs;
-    }
-}
-
-impl std::ops::Sub for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Self) -> Self::Output {
-        Self(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl std::ops::SubAssign for Position {
-    fn sub_assign(&mut self, rhs: Self) {
-        *self = *self - rhs;
-    }
-}
-
-impl std::ops::Mul<isize> for Position {
-    type Output = Position;
-
-    fn mul(self, rhs: isize) -> Self::Output {
-        Self(self.0 * rhs, self.1 * rhs)
-    }
-}
-
-impl std::ops::MulAssign<isize> for Position {
-    fn mul_assign(&mut self, rhs: isize) {
-        *self = *self * rhs;
-    }
-}
+use crate::{grid::Position, parse::ParseError, solution::Solution};
 
 #[derive(Debug, Clone)]
 struct RobotRemote {
@@ -283,41 +234,82 @@ impl DoorKeypad {
     }
 }
 
-fn sequence_number(sequence: &str) -> u64 {
-    sequence
-        .trim_end_matches('A')
-        .trim_start_matches('0')
-        .parse()
-        .expect("Could not convert sequence into u64")
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Code {
+    keys: Vec<u8>,
+    /// The numeric part of the code.
+    number: u64,
 }
 
-#[aoc(day21, part1)]
-fn part1(input: &str) -> u64 {
-    let mut keypad = DoorKeypad::build_part1();
+#[aoc_generator(day21)]
+fn parse(input: &str) -> Result<Vec<Code>, ParseError> {
     input
         .lines()
+        .map(str::trim)
         .filter(|line| !line.is_empty())
-        .map(|line| line.trim())
-        .map(|sequence| {
-            // print!("Sequence {sequence}: ");
-            keypad.count_button_presses_on_top_level(sequence.as_bytes())
-                * sequence_number(sequence)
+        .map(|line| {
+            if let Some(idx) = line.find(|c: char| !c.is_ascii_digit() && c != 'A') {
+                return Err(ParseError::at(
+                    21,
+                    input,
+                    &line[idx..],
+                    "Key is not on the door keypad",
+                ));
+            }
+
+            let digits = line.trim_end_matches('A');
+            let number = match digits {
+                "" => 0,
+                digits => digits.parse().map_err(|_| {
+                    ParseError::at(21, input, line, "Expected digits followed by 'A'")
+                })?,
+            };
+
+            Ok(Code {
+                keys: line.as_bytes().to_vec(),
+                number,
+            })
         })
+        .collect()
+}
+
+fn complexities(mut keypad: DoorKeypad, codes: &[Code]) -> u64 {
+    codes
+        .iter()
+        .map(|code| keypad.count_button_presses_on_top_level(&code.keys) * code.number)
         .sum()
 }
 
+#[aoc(day21, part1)]
+fn part1(codes: &[Code]) -> u64 {
+    complexities(DoorKeypad::build_part1(), codes)
+}
+
 #[aoc(day21, part2)]
-fn part2(input: &str) -> u64 {
-    let mut keypad = DoorKeypad::build_part2();
-    input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .map(|line| line.trim())
-        .map(|sequence| {
-            keypad.count_button_presses_on_top_level(sequence.as_bytes())
-                * sequence_number(sequence)
-        })
-        .sum()
+fn part2(codes: &[Code]) -> u64 {
+    complexities(DoorKeypad::build_part2(), codes)
+}
+
+pub struct Day21;
+
+impl Solution for Day21 {
+    const DAY: u8 = 21;
+
+    type Input<'i> = Vec<Code>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
 }
 
 #[cfg(test)]
@@ -332,26 +324,38 @@ mod tests {
 
     #[test]
     fn part1_example1() {
-        assert_eq!(part1(EXAMPLE1), 68 * 29);
+        assert_eq!(part1(&parse(EXAMPLE1).unwrap()), 68 * 29);
     }
 
     #[test]
     fn part1_example2() {
-        assert_eq!(part1(EXAMPLE2), 60 * 980);
+        assert_eq!(part1(&parse(EXAMPLE2).unwrap()), 60 * 980);
     }
 
     #[test]
     fn part1_example3() {
-        assert_eq!(part1(EXAMPLE3), 68 * 179);
+        assert_eq!(part1(&parse(EXAMPLE3).unwrap()), 68 * 179);
     }
 
     #[test]
     fn part1_example4() {
-        assert_eq!(part1(EXAMPLE4), 64 * 456);
+        assert_eq!(part1(&parse(EXAMPLE4).unwrap()), 64 * 456);
     }
 
     #[test]
     fn part1_example5() {
-        assert_eq!(part1(EXAMPLE5), 64 * 379);
+        assert_eq!(part1(&parse(EXAMPLE5).unwrap()), 64 * 379);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            parse("029A\n98B0A").unwrap_err().to_string(),
+            "Day 21, line 2, column 3: Key is not on the door keypad"
+        );
+        assert_eq!(
+            parse("0A9A").unwrap_err().to_string(),
+            "Day 21, line 1, column 1: Expected digits followed by 'A'"
+        );
     }
 }
@@ -3,53 +3,141 @@ use std::{
     io::{self, Write},
 };
 
-use itertools::Itertools;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[aoc_generator(day22)]
-fn parse(input: &str) -> Vec<u64> {
+fn parse(input: &str) -> Result<Vec<u64>, ParseError> {
     input
         .lines()
         .filter(|line| !line.is_empty())
-        .map(|num| num.parse().expect("Could not convert to number"))
+        .map(|num| {
+            num.parse()
+                .map_err(|error| ParseError::at(22, input, num, error))
+        })
         .collect()
 }
 
 #[aoc(day22, part1)]
 fn part1(input: &[u64]) -> u64 {
-    input.iter().map(|&num| part1_process_num(num)).sum()
+    let steps = BitMatrix::step().pow(STEPS as u64);
+    input.iter().map(|&num| steps.apply(num & PRUNE_MASK)).sum()
 }
 
 const PRUNE_NUMBER: u64 = 16777216;
 const PRUNE_MASK: u64 = PRUNE_NUMBER - 1;
+const BITS: usize = PRUNE_NUMBER.trailing_zeros() as usize;
 
 const STEPS: u32 = 2000;
 
-fn part1_process_num(mut num: u64) -> u64 {
-    for _ in 0..STEPS {
-        num ^= num * 64;
-        num &= PRUNE_MASK;
+fn next_secret(mut num: u64) -> u64 {
+    num ^= num * 64;
+    num &= PRUNE_MASK;
 
-        num ^= num / 32;
+    num ^= num / 32;
 
-        num ^= num * 2048;
-        num &= PRUNE_MASK;
-    }
+    num ^= num * 2048;
+    num &= PRUNE_MASK;
 
     num
 }
 
+/// Linear map over the 24 bits of a secret, as the images of every bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BitMatrix([u64; BITS]);
+
+impl BitMatrix {
+    fn identity() -> Self {
+        Self(std::array::from_fn(|bit| 1 << bit))
+    }
+
+    /// A single step of the PRNG: only shifts, XORs and masks, so it is linear over GF(2).
+    fn step() -> Self {
+        Self(std::array::from_fn(|bit| next_secret(1 << bit)))
+    }
+
+    fn apply(&self, num: u64) -> u64 {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| num & (1 << bit) != 0)
+            .fold(0, |result, (_, column)| result ^ column)
+    }
+
+    /// Applies `other` first, then `self`.
+    fn compose(&self, other: &Self) -> Self {
+        Self(other.0.map(|column| self.apply(column)))
+    }
+
+    fn pow(&self, mut exponent: u64) -> Self {
+        let mut result = Self::identity();
+        let mut square = *self;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.compose(&square);
+            }
+
+            square = square.compose(&square);
+            exponent >>= 1;
+        }
+
+        result
+    }
+
+    /// Gauss-Jordan elimination of `[self | identity]`, `None` if `self` is singular.
+    fn inverse(&self) -> Option<Self> {
+        // rows of the matrix, each with the matching identity row in the upper bits
+        let mut rows: [u64; BITS] = std::array::from_fn(|row| {
+            let bits = (0..BITS)
+                .filter(|&column| self.0[column] & (1 << row) != 0)
+                .fold(0, |bits, column| bits | 1 << column);
+            bits | 1 << (BITS + row)
+        });
+
+        for column in 0..BITS {
+            let pivot = (column..BITS).find(|&row| rows[row] & (1 << column) != 0)?;
+            rows.swap(column, pivot);
+            for row in 0..BITS {
+                if row != column && rows[row] & (1 << column) != 0 {
+                    rows[row] ^= rows[column];
+                }
+            }
+        }
+
+        // the upper bits now hold the rows of the inverse, transpose them back to columns
+        Some(Self(std::array::from_fn(|column| {
+            (0..BITS)
+                .filter(|&row| rows[row] & (1 << (BITS + column)) != 0)
+                .fold(0, |bits, row| bits | 1 << row)
+        })))
+    }
+}
+
+/// The secret after `n` steps of `seed`, in O(log n).
+pub fn nth_secret(seed: u64, n: u64) -> u64 {
+    if n == 0 {
+        return seed;
+    }
+
+    // the bits above the 24th are pruned in the first step
+    BitMatrix::step().pow(n).apply(seed & PRUNE_MASK)
+}
+
+/// The secret `n` steps before `secret`, in O(log n).
+pub fn previous_secret(secret: u64, n: u64) -> u64 {
+    BitMatrix::step()
+        .inverse()
+        .expect("The PRNG step is invertible")
+        .pow(n)
+        .apply(secret & PRUNE_MASK)
+}
+
 type Sequence = [i8; 4];
 
 #[aoc(day22, part2)]
 fn part2(input: &[u64]) -> u64 {
-    let price_deltas = input
-        .iter()
-        .map(|&num| part2_generate_prices(num))
-        .collect_vec();
-
-    // dump_seqs(&price_deltas);
-    find_best_sequence(&price_deltas)
+    find_best_sequence(input).0
 }
 
 #[allow(unused)]
@@ -69,108 +157,80 @@ fn dump_seq(seq: &[(u8, i8)], seq_num: usize) -> io::Result<()> {
     Ok(())
 }
 
-fn find_best_sequence(price_with_deltas: &[Vec<(u8, i8)>]) -> u64 {
-    // Only 130k combinations... welp
-    let mut sequence = [-9, -9, -9, -9];
-    let mut max_profit = 0;
-    while let Some(next_seq) = next_sequence(sequence) {
-        // println!("{sequence:?} ==> {next_seq:?}");
-        sequence = next_seq;
+/// Number of distinct windows of 4 deltas, each delta being in `-9..=9`.
+const WINDOWS: usize = 19 * 19 * 19 * 19;
 
-        let profit = price_with_deltas
-            .par_iter()
-            .map(|prices| first_matching_sequence(prices, sequence))
-            .sum();
+fn delta_digit(delta: i8) -> usize {
+    (delta + 9) as usize
+}
 
-        if max_profit < profit {
-            println!("Found profit {profit} with sequence {sequence:?}");
-            // for deltas in price_with_deltas {
-            // println!("    {}", first_matching_sequence(&deltas, sequence));
-            // }
-            // println!();
+fn window_index(sequence: Sequence) -> usize {
+    sequence
+        .into_iter()
+        .fold(0, |index, delta| index * 19 + delta_digit(delta))
+}
 
-            max_profit = profit;
-        }
+fn window_sequence(mut index: usize) -> Sequence {
+    let mut sequence = [0; 4];
+    for delta in sequence.iter_mut().rev() {
+        *delta = (index % 19) as i8 - 9;
+        index /= 19;
     }
 
-    max_profit
+    sequence
 }
 
-fn next_sequence(mut sequence: Sequence) -> Option<Sequence> {
-    next_sequence_increment_pos(&mut sequence, 3);
-    loop {
-        if sequence[0] >= 9 && sequence[1] >= 1 {
-            return None;
-        }
-
-        if !(-9..=9).contains(&(sequence[0] + sequence[1])) {
-            next_sequence_increment_pos(&mut sequence, 1);
-            sequence[2] = -9;
-            sequence[3] = -9;
-            continue;
-        }
-
-        if !(-9..=9).contains(&(sequence[0] + sequence[1] + sequence[2])) {
-            next_sequence_increment_pos(&mut sequence, 2);
-            sequence[3] = -9;
-            continue;
-        }
+/// Adds the price at the first occurrence of every window of 4 deltas to `totals`.
+fn add_first_prices(prices: &[(u8, i8)], totals: &mut [u32], seen: &mut [bool]) {
+    seen.fill(false);
 
-        if !(-9..=9).contains(&(sequence[0] + sequence[1] + sequence[2] + sequence[3])) {
-            next_sequence_increment_pos(&mut sequence, 3);
-            continue;
+    for window in prices.windows(4) {
+        let index = window_index([window[0].1, window[1].1, window[2].1, window[3].1]);
+        if !seen[index] {
+            seen[index] = true;
+            totals[index] += window[3].0 as u32;
         }
-
-        return Some(sequence);
     }
 }
 
-fn next_sequence_increment_pos(sequence: &mut Sequence, pos: usize) {
-    if sequence[pos] < 9 {
-        sequence[pos] += 1;
-    } else if pos != 0 {
-        sequence[pos] = -9;
-        next_sequence_increment_pos(sequence, pos - 1);
-    } else {
-        *sequence = [9, 9, 9, 9];
-    }
-}
-
-fn first_matching_sequence(prices: &[(u8, i8)], sequence: Sequence) -> u64 {
-    for i in 3..prices.len() {
-        if prices[i - 3].1 != sequence[0] {
-            continue;
-        }
-
-        if prices[i - 2].1 != sequence[1] {
-            continue;
-        }
-
-        if prices[i - 1].1 != sequence[2] {
-            continue;
-        }
-
-        if prices[i].1 != sequence[3] {
-            continue;
-        }
-
-        return prices[i].0 as u64;
-    }
+/// Best total of bananas over all buyers and the sequence of deltas selling them.
+fn find_best_sequence(secrets: &[u64]) -> (u64, Sequence) {
+    let totals = secrets
+        .par_iter()
+        .fold(
+            || (vec![0; WINDOWS], vec![false; WINDOWS]),
+            |(mut totals, mut seen), &secret| {
+                add_first_prices(&part2_generate_prices(secret), &mut totals, &mut seen);
+                (totals, seen)
+            },
+        )
+        .map(|(totals, _)| totals)
+        .reduce(
+            || vec![0; WINDOWS],
+            |mut totals, other| {
+                totals
+                    .iter_mut()
+                    .zip(other)
+                    .for_each(|(total, other)| *total += other);
+                totals
+            },
+        );
+
+    let (index, &best) = totals
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|&(_, total)| total)
+        .expect("There is always a window");
 
-    0
+    (best as u64, window_sequence(index))
 }
 
 fn part2_generate_prices(mut num: u64) -> Vec<(u8, i8)> {
     let mut result = Vec::with_capacity((STEPS - 1) as usize);
     let mut previous = (num % 10) as i8;
     for _ in 0..STEPS {
-        num ^= num * 64;
-        num &= PRUNE_MASK;
-
-        num ^= num / 32;
-
-        num ^= num * 2048;
-        num &= PRUNE_MASK;
+        num = next_secret(num);
 
         let price = (num % 10) as u8;
         let price_i8 = price as i8;
@@ -183,6 +243,28 @@ fn part2_generate_prices(mut num: u64) -> Vec<(u8, i8)> {
     result
 }
 
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: u8 = 22;
+
+    type Input<'i> = Vec<u64>;
+    type Part1 = u64;
+    type Part2 = u64;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        part2(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -194,11 +276,53 @@ mod tests {
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(&parse(EXAMPLE)), 37327623);
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 37327623);
     }
 
     #[test]
     fn part2_example() {
         assert_eq!(part2(&[1, 2, 3, 2024]), 23);
+        assert_eq!(find_best_sequence(&[1, 2, 3, 2024]), (23, [-2, 1, -1, 3]));
+    }
+
+    #[test]
+    fn jump_ahead() {
+        assert_eq!(nth_secret(123, 1), 15887950);
+        assert_eq!(nth_secret(123, 10), 5908254);
+        assert_eq!(nth_secret(2024, 2000), 8667524);
+        assert_eq!(nth_secret(123, 0), 123);
+
+        assert_eq!(previous_secret(5908254, 10), 123);
+        assert_eq!(previous_secret(15887950, 1), 123);
+
+        // the step is a permutation of the non-zero secrets with a single cycle
+        assert_eq!(nth_secret(123, PRUNE_MASK), 123);
+        let far = nth_secret(123, 3_000_000_000);
+        assert_eq!(far, nth_secret(123, 3_000_000_000 % PRUNE_MASK));
+        assert_eq!(previous_secret(far, 3_000_000_000), 123);
+
+        let step = BitMatrix::step();
+        assert_eq!(
+            step.inverse().unwrap().compose(&step),
+            BitMatrix::identity()
+        );
+    }
+
+    #[test]
+    fn window_indices() {
+        assert_eq!(window_index([-9, -9, -9, -9]), 0);
+        assert_eq!(window_index([9, 9, 9, 9]), WINDOWS - 1);
+        for sequence in [[-2, 1, -1, 3], [0, 0, 0, 0], [9, -9, 4, -3]] {
+            assert_eq!(window_sequence(window_index(sequence)), sequence);
+        }
+
+        // the prices of 123 start with 3, 0, 6, 5, 4, 4, 6
+        let mut totals = vec![0; WINDOWS];
+        add_first_prices(
+            &part2_generate_prices(123),
+            &mut totals,
+            &mut vec![false; WINDOWS],
+        );
+        assert_eq!(totals[window_index([-1, -1, 0, 2])], 6);
     }
 }
@@ -1,8 +1,10 @@
 use fnv::{FnvHashMap, FnvHashSet};
 use itertools::Itertools;
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Debug, Clone, Default)]
-struct Graph<'s> {
+pub struct Graph<'s> {
     adjacency_list: FnvHashMap<&'s str, FnvHashSet<&'s str>>,
 }
 
@@ -138,7 +140,7 @@ fn in_sorted_order<T: Ord>(a: T, b: T, c: T) -> (T, T, T) {
         .expect("3 element array not collected to 3 element tuple")
 }
 
-fn parse(input: &str) -> Graph<'_> {
+fn parse(input: &str) -> Result<Graph<'_>, ParseError> {
     let mut graph = Graph::default();
     for line in input.lines() {
         if line.is_empty() {
@@ -147,11 +149,11 @@ fn parse(input: &str) -> Graph<'_> {
 
         let (src, dst) = line
             .split_once('-')
-            .expect("Could not split connection on '-'");
+            .ok_or_else(|| ParseError::at(23, input, line, "Could not split connection on '-'"))?;
         graph.add_edge(src, dst);
     }
 
-    graph
+    Ok(graph)
 }
 
 fn keep_only_sets_with_node_starting_with_t<'s>(
@@ -163,16 +165,12 @@ fn keep_only_sets_with_node_starting_with_t<'s>(
     sets_of_k3
 }
 
-#[aoc(day23, part1)]
-fn part1(input: &str) -> usize {
-    let graph = parse(input);
+fn count_sets_with_t(graph: &Graph) -> usize {
     let k3 = graph.all_sets_of_k3();
     keep_only_sets_with_node_starting_with_t(k3).len()
 }
 
-#[aoc(day23, part2)]
-fn part2(input: &str) -> String {
-    let graph = parse(input);
+fn password(graph: &Graph) -> String {
     let mut largest = graph.find_largest_clique().into_iter().collect_vec();
     largest.sort_unstable();
 
@@ -185,6 +183,38 @@ fn part2(input: &str) -> String {
     result
 }
 
+#[aoc(day23, part1)]
+fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(count_sets_with_t(&parse(input)?))
+}
+
+#[aoc(day23, part2)]
+fn part2(input: &str) -> Result<String, ParseError> {
+    Ok(password(&parse(input)?))
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    const DAY: u8 = 23;
+
+    type Input<'i> = Graph<'i>;
+    type Part1 = usize;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        count_sets_with_t(input)
+    }
+
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        password(input)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,7 +254,7 @@ td-yn";
 
     #[test]
     fn part1_example() {
-        let graph = parse(EXAMPLE);
+        let graph = parse(EXAMPLE).unwrap();
         let sets_of_k3 = graph.all_sets_of_k3();
         assert_eq!(sets_of_k3.len(), 12);
 
@@ -234,6 +264,6 @@ td-yn";
 
     #[test]
     fn part2_example() {
-        assert_eq!(part2(EXAMPLE), "co,de,ka,ta");
+        assert_eq!(part2(EXAMPLE).unwrap(), "co,de,ka,ta");
     }
 }
@@ -1,9 +1,12 @@
 use std::cell::Cell;
+use std::error::Error;
 use std::io::Write;
 
 use fnv::FnvHashMap;
 use itertools::Itertools;
 
+use crate::{parse::ParseError, solution::Solution};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum LogicalOp {
     And,
@@ -25,12 +28,46 @@ impl std::fmt::Display for LogicalOp {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+enum WireState {
+    #[default]
+    Unknown,
+    Evaluating,
+    Known(bool),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationError {
+    Cycle { wire: String },
+    UnknownWire { wire: String },
+    TooWide { bits: usize },
+}
+
+impl std::fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cycle { wire } => write!(f, "Wire {wire} depends on its own value"),
+            Self::UnknownWire { wire } => {
+                write!(f, "Wire {wire} is neither an input nor a gate output")
+            }
+            Self::TooWide { bits } => {
+                write!(
+                    f,
+                    "Netlist has {bits} output bits, which does not fit in a u64"
+                )
+            }
+        }
+    }
+}
+
+impl Error for SimulationError {}
+
 #[derive(Debug, Clone)]
 struct LogicGate<'s> {
     left: &'s str,
     right: &'s str,
     op: LogicalOp,
-    generated_output: Cell<Option<bool>>,
+    state: Cell<WireState>,
 }
 
 impl<'s> LogicGate<'s> {
@@ -39,79 +76,221 @@ impl<'s> LogicGate<'s> {
             left,
             right,
             op,
-            generated_output: Cell::new(None),
+            state: Cell::default(),
         }
     }
 
-    fn parse_logic_gate(s: &'s str) -> (&'s str, Self) {
-        let (operation, target) = s.split_once(" -> ").expect("Could not split on \" -> \"");
-        if let Some((left, right)) = operation.split_once(" AND ") {
-            return (
-                target.trim(),
-                Self::new(left.trim(), right.trim(), LogicalOp::And),
-            );
-        }
-
-        if let Some((left, right)) = operation.split_once(" OR ") {
-            return (
-                target.trim(),
-                Self::new(left.trim(), right.trim(), LogicalOp::Or),
-            );
-        }
-
-        if let Some((left, right)) = operation.split_once(" XOR ") {
-            return (
-                target.trim(),
-                Self::new(left.trim(), right.trim(), LogicalOp::Xor),
-            );
+    fn parse_logic_gate(input: &str, s: &'s str) -> Result<(&'s str, Self), ParseError> {
+        let (operation, target) = s
+            .split_once(" -> ")
+            .ok_or_else(|| ParseError::at(24, input, s, "Could not split on \" -> \""))?;
+        for (separator, op) in [
+            (" AND ", LogicalOp::And),
+            (" OR ", LogicalOp::Or),
+            (" XOR ", LogicalOp::Xor),
+        ] {
+            if let Some((left, right)) = operation.split_once(separator) {
+                return Ok((target.trim(), Self::new(left.trim(), right.trim(), op)));
+            }
         }
 
-        panic!("Operation was neither 'AND', 'OR' not 'XOR' ({operation:?})")
+        Err(ParseError::at(
+            24,
+            input,
+            operation,
+            "Operation was neither 'AND', 'OR' nor 'XOR'",
+        ))
     }
 }
 
 impl LogicGate<'_> {
-    fn wire_value(&self, gates: &AllGates<'_>) -> bool {
-        if let Some(cached) = self.generated_output.get() {
-            return cached;
+    fn wire_value(&self, name: &str, gates: &AllGates<'_>) -> Result<bool, SimulationError> {
+        match self.state.get() {
+            WireState::Known(cached) => return Ok(cached),
+            WireState::Evaluating => {
+                return Err(SimulationError::Cycle {
+                    wire: name.to_owned(),
+                })
+            }
+            WireState::Unknown => self.state.set(WireState::Evaluating),
         }
 
-        let left = gates.lookup(self.left);
+        let left = gates.lookup(self.left)?;
+        let right = gates.lookup(self.right)?;
         let result = match self.op {
-            LogicalOp::And => left && gates.lookup(self.right),
-            LogicalOp::Or => left || gates.lookup(self.right),
-            LogicalOp::Xor => left != gates.lookup(self.right),
+            LogicalOp::And => left && right,
+            LogicalOp::Or => left || right,
+            LogicalOp::Xor => left != right,
         };
 
-        self.generated_output.set(Some(result));
-        result
+        self.state.set(WireState::Known(result));
+        Ok(result)
     }
 }
 
+fn wire_index(wire: &str, prefix: char) -> Option<usize> {
+    wire.strip_prefix(prefix)?.parse().ok()
+}
+
 #[derive(Debug)]
-struct AllGates<'s> {
+pub struct AllGates<'s> {
     initial_values: FnvHashMap<&'s str, bool>,
     mapping: FnvHashMap<&'s str, LogicGate<'s>>,
 }
 
-impl AllGates<'_> {
-    fn lookup(&self, gate: &str) -> bool {
-        if let Some(gate) = self.mapping.get(gate) {
-            gate.wire_value(self)
+impl<'s> AllGates<'s> {
+    fn lookup(&self, gate: &str) -> Result<bool, SimulationError> {
+        if let Some(logic_gate) = self.mapping.get(gate) {
+            logic_gate.wire_value(gate, self)
         } else if let Some(init) = self.initial_values.get(gate) {
-            *init
+            Ok(*init)
         } else {
-            false
+            Err(SimulationError::UnknownWire {
+                wire: gate.to_owned(),
+            })
+        }
+    }
+
+    fn wires(&self) -> impl Iterator<Item = &'s str> + '_ {
+        self.initial_values
+            .keys()
+            .chain(self.mapping.keys())
+            .copied()
+            .chain(
+                self.mapping
+                    .values()
+                    .flat_map(|gate| [gate.left, gate.right]),
+            )
+    }
+
+    fn width(&self, prefix: char) -> usize {
+        self.wires()
+            .filter_map(|wire| wire_index(wire, prefix))
+            .max()
+            .map_or(0, |index| index + 1)
+    }
+
+    pub fn input_width(&self) -> usize {
+        self.width('x').max(self.width('y'))
+    }
+
+    pub fn output_width(&self) -> usize {
+        self.width('z')
+    }
+
+    /// Forgets the cached wire values, to be called after changing inputs or gates.
+    pub fn reset(&self) {
+        for gate in self.mapping.values() {
+            gate.state.set(WireState::Unknown);
+        }
+    }
+
+    /// Sets the `x` and `y` input wires to the bits of `x` and `y`.
+    pub fn set_inputs(&mut self, x: u64, y: u64) {
+        let inputs = self
+            .wires()
+            .filter_map(|wire| {
+                let (value, index) = match (wire_index(wire, 'x'), wire_index(wire, 'y')) {
+                    (Some(index), _) => (x, index),
+                    (_, Some(index)) => (y, index),
+                    _ => return None,
+                };
+                Some((wire, index < 64 && (value >> index) & 1 == 1))
+            })
+            .collect_vec();
+
+        self.initial_values.extend(inputs);
+        self.reset();
+    }
+
+    pub fn swap_outputs(&mut self, a: &'s str, b: &'s str) {
+        let gate_a = self.mapping.remove(a);
+        let gate_b = self.mapping.remove(b);
+        if let Some(gate) = gate_a {
+            self.mapping.insert(b, gate);
+        }
+
+        if let Some(gate) = gate_b {
+            self.mapping.insert(a, gate);
         }
+
+        self.reset();
     }
 
-    fn get_number(&self) -> u64 {
+    fn get_number(&self) -> Result<u64, SimulationError> {
+        let bits = self.output_width();
+        if bits > u64::BITS as usize {
+            return Err(SimulationError::TooWide { bits });
+        }
+
         let mut result = 0;
-        for i in 0..u64::BITS {
-            result |= (self.lookup(&format!("z{i:0>2}")) as u64) << i;
+        for i in 0..bits {
+            result |= (self.lookup(&format!("z{i:0>2}"))? as u64) << i;
+        }
+
+        Ok(result)
+    }
+
+    /// Value of the `z` wires with `x` and `y` as inputs.
+    pub fn evaluate(&mut self, x: u64, y: u64) -> Result<u64, SimulationError> {
+        self.set_inputs(x, y);
+        self.get_number()
+    }
+
+    /// Mask of the output bits that differ from `x + y`.
+    pub fn wrong_bits(&mut self, x: u64, y: u64) -> Result<u64, SimulationError> {
+        let bits = self.output_width().min(u64::BITS as usize);
+        let mask = match bits {
+            0 => 0,
+            bits => u64::MAX >> (u64::BITS as usize - bits),
+        };
+        Ok((self.evaluate(x, y)? ^ x.wrapping_add(y)) & mask)
+    }
+
+    /// Checks every gate against the role it has in a ripple-carry adder:
+    /// `z_i = (x_i ^ y_i) ^ carry_i` and `carry_i+1 = (x_i & y_i) | ((x_i ^ y_i) & carry_i)`,
+    /// except for the half adder of bit 0 and the last carry which is the highest z.
+    fn find_miswired_outputs(&self) -> Vec<&'s str> {
+        let is_input = |wire: &str| wire.starts_with('x') || wire.starts_with('y');
+        let is_first_bit = |wire: &str| wire == "x00" || wire == "y00";
+        let highest_z = self
+            .mapping
+            .keys()
+            .filter(|wire| wire.starts_with('z'))
+            .max()
+            .copied()
+            .unwrap_or_default();
+
+        let mut consumers: FnvHashMap<&str, Vec<LogicalOp>> = FnvHashMap::default();
+        for gate in self.mapping.values() {
+            consumers.entry(gate.left).or_default().push(gate.op);
+            consumers.entry(gate.right).or_default().push(gate.op);
+        }
+        let feeds = |wire: &str, op: LogicalOp| {
+            consumers
+                .get(wire)
+                .is_some_and(|consumers| consumers.contains(&op))
+        };
+
+        let mut miswired = vec![];
+        for (&output, gate) in self.mapping.iter() {
+            let from_inputs = is_input(gate.left) && is_input(gate.right);
+            let first_bit = is_first_bit(gate.left) && is_first_bit(gate.right);
+            let is_wrong = match gate.op {
+                _ if output == highest_z => gate.op != LogicalOp::Or,
+                LogicalOp::And | LogicalOp::Or if output.starts_with('z') => true,
+                LogicalOp::Xor if !from_inputs => !output.starts_with('z'),
+                LogicalOp::Xor if !first_bit => !feeds(output, LogicalOp::Xor),
+                LogicalOp::And if !first_bit => !feeds(output, LogicalOp::Or),
+                _ => false,
+            };
+
+            if is_wrong {
+                miswired.push(output);
+            }
         }
 
-        result
+        miswired
     }
 
     #[allow(unused)]
@@ -148,76 +327,79 @@ impl std::fmt::Display for AllGates<'_> {
     }
 }
 
-fn parse(input: &str) -> AllGates<'_> {
+fn parse(input: &str) -> Result<AllGates<'_>, ParseError> {
     let mut lines = input.lines();
     let mut initial = FnvHashMap::default();
     for line in lines.by_ref().take_while(|line| !line.is_empty()) {
-        let (name, value) = line
-            .split_once(':')
-            .expect("Could not split initial value on ':'");
-        initial.insert(
-            name,
-            value
-                .trim()
-                .parse::<u8>()
-                .expect("Could not parse value to 0 or 1")
-                != 0,
-        );
+        let (name, value) = line.split_once(':').ok_or_else(|| {
+            ParseError::at(24, input, line, "Could not split initial value on ':'")
+        })?;
+        let value = value.trim();
+        let value = match value {
+            "0" => false,
+            "1" => true,
+            _ => {
+                return Err(ParseError::at(
+                    24,
+                    input,
+                    value,
+                    "Value was neither 0 nor 1",
+                ))
+            }
+        };
+
+        initial.insert(name, value);
     }
 
     let mut gates = FnvHashMap::default();
     for line in lines.filter(|line| !line.is_empty()) {
-        let (name, gate) = LogicGate::parse_logic_gate(line.trim());
+        let (name, gate) = LogicGate::parse_logic_gate(input, line.trim())?;
         gates.insert(name, gate);
     }
 
-    AllGates {
+    Ok(AllGates {
         initial_values: initial,
         mapping: gates,
-    }
+    })
 }
 
 #[aoc(day24, part1)]
-fn part1(input: &str) -> u64 {
-    let gates = parse(input);
-    gates.get_number()
+fn part1(input: &str) -> Result<u64, Box<dyn Error>> {
+    let gates = parse(input)?;
+    Ok(gates.get_number()?)
 }
 
-#[allow(unused)]
-#[aoc(day24, part2, hard_coded)]
-fn part2_hard_coded(input: &str) -> String {
-    // Those were found by manually inspecting the graphviz output of the connections
-    const SWAPPED_PAIRS: [(&str, &str); 4] = [
-        // Anomaly near x33, y33 and z33
-        ("cqm", "vjv"),
-        // Anomaly near x25, y25 and z25
-        ("z25", "mps"),
-        // Anomaly near x19, y19 and z19
-        ("z19", "vwp"),
-        // Anomaly near x13, y13 and z13
-        ("z13", "vcv"),
-    ];
-
-    // let gates = parse(input);
-    // gates
-    // .dump_to_file("input.gv")
-    // .expect("Could not write graph to external file");
-
-    let mut swapped: [&str; 8] = SWAPPED_PAIRS
-        .into_iter()
-        .flat_map(|(a, b)| [a, b])
-        .collect_vec()
-        .try_into()
-        .expect("SWAPPED_PAIRS did not generate 8 values");
-
-    swapped.sort_unstable();
-    let mut result = swapped[0].to_owned();
-    for el in &swapped[1..] {
-        result.push(',');
-        result.push_str(el);
+fn miswired_wires(gates: &AllGates) -> String {
+    gates.find_miswired_outputs().into_iter().sorted().join(",")
+}
+
+#[aoc(day24, part2)]
+fn part2(input: &str) -> Result<String, ParseError> {
+    Ok(miswired_wires(&parse(input)?))
+}
+
+pub struct Day24;
+
+impl Solution for Day24 {
+    const DAY: u8 = 24;
+
+    type Input<'i> = AllGates<'i>;
+    type Part1 = Result<u64, SimulationError>;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        // wire values of a previous call are still cached
+        input.reset();
+        input.get_number()
     }
 
-    result
+    fn part2(input: &Self::Input<'_>) -> Self::Part2 {
+        miswired_wires(input)
+    }
 }
 
 #[cfg(test)]
@@ -285,7 +467,136 @@ tnw OR pbm -> gnj";
 
     #[test]
     fn part1_example() {
-        assert_eq!(part1(EXAMPLE1), 4);
-        assert_eq!(part1(EXAMPLE2), 2024);
+        assert_eq!(part1(EXAMPLE1).unwrap(), 4);
+        assert_eq!(part1(EXAMPLE2).unwrap(), 2024);
+    }
+
+    /// Ripple-carry adder of `bits` bits where the outputs of each pair in `swaps` are exchanged.
+    fn adder_netlist(bits: usize, swaps: &[(&str, &str)]) -> String {
+        let mut netlist = String::new();
+        for input in ["x", "y"] {
+            for bit in 0..bits {
+                netlist.push_str(&format!("{input}{bit:0>2}: 0\n"));
+            }
+        }
+
+        netlist.push('\n');
+        let mut gates = vec![
+            ("x00 XOR y00".to_owned(), "z00".to_owned()),
+            ("x00 AND y00".to_owned(), "c01".to_owned()),
+        ];
+        for bit in 1..bits {
+            let carry_out = if bit == bits - 1 {
+                format!("z{bits:0>2}")
+            } else {
+                format!("c{:0>2}", bit + 1)
+            };
+
+            gates.extend([
+                (format!("x{bit:0>2} XOR y{bit:0>2}"), format!("s{bit:0>2}")),
+                (format!("x{bit:0>2} AND y{bit:0>2}"), format!("a{bit:0>2}")),
+                (format!("s{bit:0>2} XOR c{bit:0>2}"), format!("z{bit:0>2}")),
+                (format!("s{bit:0>2} AND c{bit:0>2}"), format!("p{bit:0>2}")),
+                (format!("a{bit:0>2} OR p{bit:0>2}"), carry_out),
+            ]);
+        }
+
+        for (operation, mut output) in gates {
+            for &(a, b) in swaps {
+                if output == a {
+                    output = b.to_owned();
+                } else if output == b {
+                    output = a.to_owned();
+                }
+            }
+
+            netlist.push_str(&format!("{operation} -> {output}\n"));
+        }
+
+        netlist
+    }
+
+    #[test]
+    fn simulate_netlist() {
+        let netlist = adder_netlist(8, &[]);
+        let mut gates = parse(&netlist).unwrap();
+        assert_eq!((gates.input_width(), gates.output_width()), (8, 9));
+        assert_eq!(gates.evaluate(200, 100), Ok(300));
+        assert_eq!(gates.evaluate(255, 255), Ok(510));
+        assert_eq!(gates.wrong_bits(123, 45), Ok(0));
+
+        let mut gates = parse(EXAMPLE1).unwrap();
+        assert_eq!(gates.evaluate(0b101, 0b010), Ok(0b110));
+
+        let netlist = "x00: 1\n\nx00 AND b -> a\na OR x00 -> b\na XOR b -> z00";
+        let mut cycle = parse(netlist).unwrap();
+        assert_eq!(
+            cycle.evaluate(1, 0),
+            Err(SimulationError::Cycle { wire: "a".into() })
+        );
+        // a failed run must not poison the next ones: a and z00 are left mid-evaluation
+        cycle
+            .mapping
+            .insert("b", LogicGate::new("x00", "y00", LogicalOp::Or));
+        assert_eq!(cycle.evaluate(1, 0), Ok(0));
+        assert_eq!(cycle.wrong_bits(1, 0), Ok(1));
+
+        // neither may the solver's part 1
+        let mut cycle = parse(netlist).unwrap();
+        assert!(Day24::part1(&cycle).is_err());
+        cycle
+            .mapping
+            .insert("b", LogicGate::new("x00", "x00", LogicalOp::Or));
+        assert_eq!(Day24::part1(&cycle), Ok(0));
+
+        let unknown = parse("x00: 1\n\nx00 AND y00 -> z00").unwrap();
+        assert_eq!(
+            Day24::part1(&unknown),
+            Err(SimulationError::UnknownWire { wire: "y00".into() })
+        );
+        assert_eq!(
+            Day24::part1(&unknown).unwrap_err().to_string(),
+            "Wire y00 is neither an input nor a gate output"
+        );
+
+        let mut no_outputs = parse("x00: 1\n\nx00 AND y00 -> a").unwrap();
+        assert_eq!(no_outputs.wrong_bits(1, 1), Ok(0));
+    }
+
+    #[test]
+    fn part2_repairs_adder() {
+        assert_eq!(part2(&adder_netlist(32, &[])).unwrap(), "");
+
+        let swaps = [
+            ("z05", "a05"),
+            ("s10", "a10"),
+            ("z20", "c21"),
+            ("z30", "p30"),
+        ];
+        assert_eq!(
+            part2(&adder_netlist(32, &swaps)).unwrap(),
+            "a05,a10,c21,p30,s10,z05,z20,z30"
+        );
+
+        // cross-check the structural rules with the simulator
+        let netlist = adder_netlist(32, &swaps);
+        let mut gates = parse(&netlist).unwrap();
+        let samples = [
+            (0, 0),
+            (1 << 5, 1 << 5),
+            (0xffff_ffff, 1),
+            (0x1234_5678, 0x0fed_cba9),
+        ];
+        assert!(samples
+            .iter()
+            .any(|&(x, y)| gates.wrong_bits(x, y) != Ok(0)));
+
+        for (a, b) in swaps {
+            gates.swap_outputs(a, b);
+        }
+
+        for (x, y) in samples {
+            assert_eq!(gates.wrong_bits(x, y), Ok(0));
+        }
     }
 }
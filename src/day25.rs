@@ -0,0 +1,224 @@
+use std::{error::Error, str::FromStr};
+
+use itertools::Itertools;
+
+use crate::{parse::ParseError, solution::Solution};
+
+const PIN_COUNT: usize = 5;
+const SCHEMATIC_HEIGHT: usize = 7;
+const AVAILABLE_SPACE: u8 = (SCHEMATIC_HEIGHT - 2) as u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Lock {
+    pin_heights: [u8; PIN_COUNT],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    heights: [u8; PIN_COUNT],
+}
+
+impl Lock {
+    fn fits(&self, key: &Key) -> bool {
+        self.pin_heights
+            .iter()
+            .zip(key.heights.iter())
+            .all(|(pin, key)| pin + key <= AVAILABLE_SPACE)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Schematic {
+    Lock(Lock),
+    Key(Key),
+}
+
+impl FromStr for Schematic {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = s.lines().map(str::trim).collect_vec();
+        if rows.len() != SCHEMATIC_HEIGHT {
+            return Err(format!(
+                "Schematic has {} rows instead of {SCHEMATIC_HEIGHT}",
+                rows.len()
+            )
+            .into());
+        }
+
+        let mut heights = [0; PIN_COUNT];
+        for row in rows.iter() {
+            if row.len() != PIN_COUNT {
+                return Err(format!("Row {row:?} is not {PIN_COUNT} characters wide").into());
+            }
+
+            for (height, c) in heights.iter_mut().zip(row.bytes()) {
+                match c {
+                    b'#' => *height += 1,
+                    b'.' => (),
+                    _ => return Err(format!("{:?} was neither '#' nor '.'", c as char).into()),
+                }
+            }
+        }
+
+        let is_filled = |row: &str| row.bytes().all(|c| c == b'#');
+        let is_lock = is_filled(rows[0]);
+        if !is_lock && !is_filled(rows[SCHEMATIC_HEIGHT - 1]) {
+            return Err("Schematic has neither its top nor its bottom row filled".into());
+        }
+
+        // the filled top (lock) or bottom (key) row is not part of the height
+        heights.iter_mut().for_each(|height| *height -= 1);
+
+        if is_lock {
+            Ok(Self::Lock(Lock {
+                pin_heights: heights,
+            }))
+        } else {
+            Ok(Self::Key(Key { heights }))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schematics {
+    locks: Vec<Lock>,
+    keys: Vec<Key>,
+}
+
+impl Schematics {
+    fn count_fitting_pairs(&self) -> usize {
+        self.locks
+            .iter()
+            .cartesian_product(self.keys.iter())
+            .filter(|(lock, key)| lock.fits(key))
+            .count()
+    }
+}
+
+#[aoc_generator(day25)]
+fn parse(input: &str) -> Result<Schematics, ParseError> {
+    let mut schematics = Schematics::default();
+    for block in input.split("\n\n").filter(|block| !block.trim().is_empty()) {
+        match block
+            .parse()
+            .map_err(|error| ParseError::at(25, input, block, error))?
+        {
+            Schematic::Lock(lock) => schematics.locks.push(lock),
+            Schematic::Key(key) => schematics.keys.push(key),
+        }
+    }
+
+    Ok(schematics)
+}
+
+#[aoc(day25, part1)]
+fn part1(schematics: &Schematics) -> usize {
+    schematics.count_fitting_pairs()
+}
+
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: u8 = 25;
+
+    type Input<'i> = Schematics;
+    type Part1 = usize;
+    type Part2 = ();
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError> {
+        parse(input)
+    }
+
+    fn part1(input: &Self::Input<'_>) -> Self::Part1 {
+        part1(input)
+    }
+
+    fn part2(_input: &Self::Input<'_>) -> Self::Part2 {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "#####
+.####
+.####
+.####
+.#.#.
+.#...
+.....
+
+#####
+##.##
+.#.##
+...##
+...#.
+...#.
+.....
+
+.....
+#....
+#....
+#...#
+#.#.#
+#.###
+#####
+
+.....
+.....
+#.#..
+###..
+###.#
+###.#
+#####
+
+.....
+.....
+.....
+#....
+#.#..
+#.#.#
+#####";
+
+    #[test]
+    fn parse_example() {
+        let schematics = parse(EXAMPLE).unwrap();
+        assert_eq!(
+            schematics.locks,
+            [
+                Lock {
+                    pin_heights: [0, 5, 3, 4, 3]
+                },
+                Lock {
+                    pin_heights: [1, 2, 0, 5, 3]
+                },
+            ]
+        );
+        assert_eq!(
+            schematics.keys,
+            [
+                Key {
+                    heights: [5, 0, 2, 1, 3]
+                },
+                Key {
+                    heights: [4, 3, 4, 0, 2]
+                },
+                Key {
+                    heights: [3, 0, 2, 0, 1]
+                },
+            ]
+        );
+
+        let error = parse(".####\n.####\n.####\n.####\n.#.#.\n.#...\n.....").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Day 25, line 1, column 1: Schematic has neither its top nor its bottom row filled"
+        );
+    }
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(part1(&parse(EXAMPLE).unwrap()), 3);
+    }
+}
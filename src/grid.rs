@@ -0,0 +1,421 @@
+use std::{convert::Infallible, error::Error, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position(pub isize, pub isize);
+
+pub const DIRECT_NEIGHBORS: [Position; 4] = [
+    Position(1, 0),
+    Position(0, 1),
+    Position(-1, 0),
+    Position(0, -1),
+];
+
+pub const ALL_NEIGHBORS: [Position; 8] = [
+    Position(1, 0),
+    Position(1, 1),
+    Position(0, 1),
+    Position(-1, 1),
+    Position(-1, 0),
+    Position(-1, -1),
+    Position(0, -1),
+    Position(1, -1),
+];
+
+impl Position {
+    pub fn manhattan_distance(&self, other: &Self) -> u64 {
+        (self.0.abs_diff(other.0) + self.1.abs_diff(other.1)) as u64
+    }
+
+    pub fn chebyshev_distance(&self, other: &Self) -> u64 {
+        self.0.abs_diff(other.0).max(self.1.abs_diff(other.1)) as u64
+    }
+
+    /// Both coordinates as indices, panicking if one is negative.
+    pub fn into_usize_tuple(self) -> (usize, usize) {
+        if self.0 < 0 || self.1 < 0 {
+            panic!("Cannot convert {self} to usize pair");
+        }
+
+        (self.0 as usize, self.1 as usize)
+    }
+
+    pub fn wrap(&mut self, dimension: Position) {
+        self.0 = self.0.rem_euclid(dimension.0);
+        self.1 = self.1.rem_euclid(dimension.1);
+    }
+
+    pub fn direct_neighbors(self) -> impl Iterator<Item = Position> {
+        DIRECT_NEIGHBORS.into_iter().map(move |delta| self + delta)
+    }
+
+    pub fn all_neighbors(self) -> impl Iterator<Item = Position> {
+        ALL_NEIGHBORS.into_iter().map(move |delta| self + delta)
+    }
+
+    pub fn positions_within(self, max_distance: usize) -> impl Iterator<Item = (Position, u64)> {
+        let max_isize = max_distance as isize;
+        ((self.0 - max_isize)..=(self.0 + max_isize))
+            .flat_map(move |x_dist| {
+                ((self.1 - max_isize)..=(self.1 + max_isize))
+                    .map(move |y_dist| Position(x_dist, y_dist))
+            })
+            .map(move |position| (position, self.manhattan_distance(&position)))
+            .filter(move |(_, dist)| (*dist as usize) <= max_distance)
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
+}
+
+impl std::ops::Add for Position {
+    type Output = Position;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl std::ops::AddAssign for Position {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::Sub for Position {
+    type Output = Position;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl std::ops::SubAssign for Position {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl std::ops::Mul<isize> for Position {
+    type Output = Position;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Self(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl std::ops::MulAssign<isize> for Position {
+    fn mul_assign(&mut self, rhs: isize) {
+        *self = *self * rhs;
+    }
+}
+
+impl FromStr for Position {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((x, y)) = s.split_once(',') else {
+            return Err(format!("{s:?} does not contain a ','").into());
+        };
+
+        Ok(Self(x.trim().parse()?, y.trim().parse()?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridParseError<E> {
+    InvalidCell {
+        position: Position,
+        error: E,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for GridParseError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidCell { position, error } => {
+                write!(f, "Invalid cell at {position}: {error}")
+            }
+            Self::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Row {row} has {found} cells, expected {expected} like the first row"
+            ),
+        }
+    }
+}
+
+impl<E: std::fmt::Debug + std::fmt::Display> Error for GridParseError<E> {}
+
+/// Rectangular grid indexed by `Position(row, column)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    pub fn from_elem(height: usize, width: usize, elem: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![elem; height * width],
+            width,
+            height,
+        }
+    }
+
+    /// Parses one cell per byte, skipping empty lines and trimming the others.
+    pub fn parse_with<E, F>(input: &str, mut parse_cell: F) -> Result<Self, GridParseError<E>>
+    where
+        F: FnMut(Position, u8) -> Result<T, E>,
+    {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+        for (row_idx, line) in input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+        {
+            let expected = *width.get_or_insert(line.len());
+            if line.len() != expected {
+                return Err(GridParseError::RaggedRow {
+                    row: row_idx,
+                    expected,
+                    found: line.len(),
+                });
+            }
+
+            for (col_idx, c) in line.bytes().enumerate() {
+                let position = Position(row_idx as isize, col_idx as isize);
+                cells.push(
+                    parse_cell(position, c)
+                        .map_err(|error| GridParseError::InvalidCell { position, error })?,
+                );
+            }
+
+            height += 1;
+        }
+
+        Ok(Self {
+            cells,
+            width: width.unwrap_or(0),
+            height,
+        })
+    }
+
+    pub fn try_parse(input: &str) -> Result<Self, GridParseError<T::Error>>
+    where
+        T: TryFrom<u8>,
+    {
+        Self::parse_with(input, |_, c| T::try_from(c))
+    }
+
+    pub fn parse(input: &str) -> Result<Self, GridParseError<Infallible>>
+    where
+        T: From<u8>,
+    {
+        Self::parse_with(input, |_, c| Ok(T::from(c)))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, index: Position) -> bool {
+        index.0 >= 0
+            && index.1 >= 0
+            && (index.0 as usize) < self.height
+            && (index.1 as usize) < self.width
+    }
+
+    fn offset(&self, index: Position) -> Option<usize> {
+        self.contains(index)
+            .then(|| index.0 as usize * self.width + index.1 as usize)
+    }
+
+    pub fn get(&self, index: Position) -> Option<&T> {
+        self.cells.get(self.offset(index)?)
+    }
+
+    pub fn get_mut(&mut self, index: Position) -> Option<&mut T> {
+        let offset = self.offset(index)?;
+        self.cells.get_mut(offset)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row_idx| {
+            (0..width).map(move |col_idx| Position(row_idx as isize, col_idx as isize))
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn find_position<P>(&self, mut predicate: P) -> Option<Position>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter()
+            .find_map(|(position, cell)| predicate(cell).then_some(position))
+    }
+
+    /// 4-connected neighbours of `index` that are inside the grid.
+    pub fn neighbours(&self, index: Position) -> impl Iterator<Item = Position> + '_ {
+        index
+            .direct_neighbors()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    /// 8-connected neighbours of `index` that are inside the grid.
+    pub fn all_neighbours(&self, index: Position) -> impl Iterator<Item = Position> + '_ {
+        index
+            .all_neighbors()
+            .filter(|neighbour| self.contains(*neighbour))
+    }
+
+    pub fn map<U, F>(self, f: F) -> Grid<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Grid {
+            cells: self.cells.into_iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+}
+
+impl<T> std::ops::Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Position) -> &Self::Output {
+        self.get(index)
+            .unwrap_or_else(|| panic!("Could not index position {index} in grid"))
+    }
+}
+
+impl<T> std::ops::IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, index: Position) -> &mut Self::Output {
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("Could not index position {index} in grid"))
+    }
+}
+
+impl<T: std::fmt::Display> std::fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Tile {
+        Wall,
+        Open,
+    }
+
+    impl TryFrom<u8> for Tile {
+        type Error = u8;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            match value {
+                b'#' => Ok(Self::Wall),
+                b'.' => Ok(Self::Open),
+                other => Err(other),
+            }
+        }
+    }
+
+    impl std::fmt::Display for Tile {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "{}",
+                match self {
+                    Self::Wall => '#',
+                    Self::Open => '.',
+                }
+            )
+        }
+    }
+
+    const EXAMPLE: &str = "###
+#..
+##.
+";
+
+    #[test]
+    fn parse_and_display() {
+        let grid = Grid::<Tile>::try_parse(EXAMPLE).unwrap();
+        assert_eq!((grid.height(), grid.width()), (3, 3));
+        assert_eq!(grid[Position(1, 1)], Tile::Open);
+        assert_eq!(grid.get(Position(3, 0)), None);
+        assert_eq!(grid.get(Position(0, -1)), None);
+        assert_eq!(grid.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            Grid::<Tile>::try_parse("#.\n#x"),
+            Err(GridParseError::InvalidCell {
+                position: Position(1, 1),
+                error: b'x'
+            })
+        );
+        assert_eq!(
+            Grid::<u8>::parse("##\n#"),
+            Err(GridParseError::RaggedRow {
+                row: 1,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = Grid::from_elem(2, 3, 0_u8);
+        let mut direct = grid.neighbours(Position(0, 0)).collect::<Vec<_>>();
+        direct.sort();
+        assert_eq!(direct, [Position(0, 1), Position(1, 0)]);
+        assert_eq!(grid.all_neighbours(Position(0, 1)).count(), 5);
+        assert_eq!(grid.all_neighbours(Position(1, 1)).count(), 5);
+    }
+}
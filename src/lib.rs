@@ -27,5 +27,14 @@ pub mod day21;
 pub mod day22;
 pub mod day23;
 pub mod day24;
+pub mod day25;
+
+pub mod grid;
+pub mod parse;
+pub mod pathfinding;
+pub mod runner;
+pub mod solution;
+#[cfg(test)]
+mod testing;
 
 aoc_lib! { year = 2024 }
@@ -0,0 +1,212 @@
+use std::{error::Error, str::FromStr};
+
+use crate::grid::{GridParseError, Position};
+
+/// Error of the `#[aoc_generator]` parsers, lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub reason: String,
+}
+
+impl ParseError {
+    pub fn new(day: u8, line: usize, column: usize, reason: impl ToString) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Locates `fragment`, which has to be a subslice of `input`, in `input`. Other fragments are
+    /// reported at the start of the input.
+    pub fn at(day: u8, input: &str, fragment: &str, reason: impl ToString) -> Self {
+        let start = input.as_ptr() as usize;
+        let offset = (fragment.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|&offset| offset <= input.len() && input.is_char_boundary(offset))
+            .unwrap_or(0);
+
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self::new(
+            day,
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+            reason,
+        )
+    }
+
+    pub fn at_end(day: u8, input: &str, reason: impl ToString) -> Self {
+        Self::at(day, input, &input[input.len()..], reason)
+    }
+
+    pub fn from_grid<E: std::fmt::Display>(day: u8, error: GridParseError<E>) -> Self {
+        match error {
+            GridParseError::InvalidCell { position, .. } => {
+                Self::new(day, position.0 as usize + 1, position.1 as usize + 1, error)
+            }
+            GridParseError::RaggedRow { row, .. } => Self::new(day, row + 1, 1, error),
+        }
+    }
+}
+
+/// Settings of a puzzle that are not part of its input, like the size of the grid, given by an
+/// optional first line of the input: `# key=value key=value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameters<'i> {
+    day: u8,
+    input: &'i str,
+    values: Vec<(&'i str, &'i str)>,
+}
+
+impl<'i> Parameters<'i> {
+    /// Splits the parameters line, if any, from the rest of `input`. Only `keys` are accepted.
+    pub fn split(day: u8, input: &'i str, keys: &[&str]) -> Result<(Self, &'i str), ParseError> {
+        let mut parameters = Self {
+            day,
+            input,
+            values: Vec::new(),
+        };
+
+        let Some(line) = input.strip_prefix('#') else {
+            return Ok((parameters, input));
+        };
+        let (line, rest) = line.split_once('\n').unwrap_or((line, ""));
+        for parameter in line.split_whitespace() {
+            let (key, value) = parameter
+                .split_once('=')
+                .ok_or_else(|| ParseError::at(day, input, parameter, "Expected key=value"))?;
+            if !keys.contains(&key) {
+                let expected = keys.join(", ");
+                return Err(ParseError::at(
+                    day,
+                    input,
+                    key,
+                    format!("Unknown parameter {key:?}, expected one of {expected}"),
+                ));
+            }
+
+            parameters.values.push((key, value));
+        }
+
+        Ok((parameters, rest))
+    }
+
+    fn value(&self, key: &str) -> Option<&'i str> {
+        self.values
+            .iter()
+            .find(|&&(name, _)| name == key)
+            .map(|&(_, value)| value)
+    }
+
+    /// The first value of `key`.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, ParseError>
+    where
+        T::Err: std::fmt::Display,
+    {
+        self.value(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|error| ParseError::at(self.day, self.input, value, error))
+            })
+            .transpose()
+    }
+
+    /// The first value of `key`, as `<width>x<height>`.
+    pub fn dimension(&self, key: &str) -> Result<Option<Position>, ParseError> {
+        let Some(value) = self.value(key) else {
+            return Ok(None);
+        };
+        let error = |reason| ParseError::at(self.day, self.input, value, reason);
+
+        let (width, height) = value
+            .split_once('x')
+            .ok_or_else(|| error("Expected <width>x<height>".to_owned()))?;
+        let parse = |side: &str| match side.parse() {
+            Ok(side) if side > 0 => Ok(side),
+            Ok(_) => Err(error("Dimensions must be positive".to_owned())),
+            Err(parse_error) => Err(error(format!("{parse_error}"))),
+        };
+
+        Ok(Some(Position(parse(width)?, parse(height)?)))
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Day {:0>2}, line {}, column {}: {}",
+            self.day, self.line, self.column, self.reason
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Grid;
+
+    #[test]
+    fn locate_fragment() {
+        let input = "12 34\n56 7x\n";
+        let fragment = &input[9..11];
+        assert_eq!(fragment, "7x");
+        assert_eq!(
+            ParseError::at(2, input, fragment, "invalid digit").to_string(),
+            "Day 02, line 2, column 4: invalid digit"
+        );
+        assert_eq!(
+            ParseError::at(2, input, "elsewhere", "reason"),
+            ParseError::new(2, 1, 1, "reason")
+        );
+    }
+
+    #[test]
+    fn parameters() {
+        let input = "# size=11x7 bytes=12\n1,2\n";
+        let (parameters, rest) = Parameters::split(18, input, &["size", "bytes"]).unwrap();
+        assert_eq!(rest, "1,2\n");
+        assert_eq!(parameters.dimension("size"), Ok(Some(Position(11, 7))));
+        assert_eq!(parameters.get::<usize>("bytes"), Ok(Some(12)));
+        assert_eq!(parameters.get::<usize>("other"), Ok(None));
+
+        let (parameters, rest) = Parameters::split(18, "1,2", &["size"]).unwrap();
+        assert_eq!(rest, "1,2");
+        assert_eq!(parameters.dimension("size"), Ok(None));
+
+        assert_eq!(
+            Parameters::split(14, "# size=11x7 speed=2\n", &["size"])
+                .unwrap_err()
+                .to_string(),
+            "Day 14, line 1, column 13: Unknown parameter \"speed\", expected one of size"
+        );
+        let (parameters, _) = Parameters::split(14, "# size=11y7", &["size"]).unwrap();
+        assert_eq!(
+            parameters.dimension("size").unwrap_err(),
+            ParseError::new(14, 1, 8, "Expected <width>x<height>")
+        );
+        let (parameters, _) = Parameters::split(14, "# size=0x7", &["size"]).unwrap();
+        assert!(parameters.dimension("size").is_err());
+    }
+
+    #[test]
+    fn grid_errors() {
+        let error = Grid::<u8>::parse_with("..\n.x", |_, c| match c {
+            b'.' => Ok(0),
+            _ => Err("not a '.'"),
+        })
+        .unwrap_err();
+        assert_eq!(
+            ParseError::from_grid(6, error),
+            ParseError::new(6, 2, 2, "Invalid cell at (1, 1): not a '.'")
+        );
+    }
+}
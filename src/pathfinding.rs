@@ -0,0 +1,302 @@
+use std::{collections::BinaryHeap, collections::VecDeque, hash::Hash};
+
+use fnv::{FnvHashMap, FnvHashSet};
+
+/// A graph of states explored lazily by the search functions of this module.
+pub trait SearchSpace {
+    type State: Copy + Eq + Hash;
+
+    /// Reachable states from `state`, along with the cost of the step.
+    fn neighbours(&self, state: &Self::State) -> impl IntoIterator<Item = (Self::State, u64)>;
+
+    /// Searches stop once a goal is reached, by default every state is explored.
+    fn is_goal(&self, _state: &Self::State) -> bool {
+        false
+    }
+
+    /// Lower bound of the cost to the nearest goal, A* expects it to be consistent.
+    fn heuristic(&self, _state: &Self::State) -> u64 {
+        0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Explored<S> {
+    pub costs: FnvHashMap<S, u64>,
+    pub predecessors: FnvHashMap<S, Vec<S>>,
+    pub goals: Vec<S>,
+}
+
+impl<S: Copy + Eq + Hash> Explored<S> {
+    fn new(start: S) -> Self {
+        Self {
+            costs: FnvHashMap::from_iter([(start, 0)]),
+            predecessors: FnvHashMap::default(),
+            goals: vec![],
+        }
+    }
+
+    pub fn cost(&self, state: &S) -> Option<u64> {
+        self.costs.get(state).copied()
+    }
+
+    pub fn goal_cost(&self) -> Option<u64> {
+        self.cost(self.goals.first()?)
+    }
+
+    /// Follows the first recorded predecessor of each state back to the start.
+    pub fn path_to(&self, state: S) -> Option<Vec<S>> {
+        if !self.costs.contains_key(&state) {
+            return None;
+        }
+
+        let mut path = vec![state];
+        let mut current = state;
+        while let Some(&previous) = self
+            .predecessors
+            .get(&current)
+            .and_then(|predecessors| predecessors.first())
+        {
+            path.push(previous);
+            current = previous;
+        }
+
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn shortest_path(&self) -> Option<(Vec<S>, u64)> {
+        let goal = *self.goals.first()?;
+        Some((self.path_to(goal)?, self.cost(&goal)?))
+    }
+
+    /// Every state that is part of at least one of the recorded paths to `targets`.
+    pub fn states_on_paths(&self, targets: impl IntoIterator<Item = S>) -> FnvHashSet<S> {
+        let mut states = FnvHashSet::default();
+        let mut stack = targets.into_iter().collect::<Vec<_>>();
+        while let Some(state) = stack.pop() {
+            if !states.insert(state) {
+                continue;
+            }
+
+            if let Some(predecessors) = self.predecessors.get(&state) {
+                stack.extend_from_slice(predecessors);
+            }
+        }
+
+        states
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct QueueEntry<S> {
+    priority: u64,
+    cost: u64,
+    state: S,
+}
+
+impl<S> PartialEq for QueueEntry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<S> Eq for QueueEntry<S> {}
+
+impl<S> PartialOrd for QueueEntry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for QueueEntry<S> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // reverse ordering
+        other.priority.cmp(&self.priority)
+    }
+}
+
+/// Breadth first search, every step costs 1 regardless of the cost given by the space.
+pub fn bfs<G: SearchSpace>(space: &G, start: G::State) -> Explored<G::State> {
+    let mut explored = Explored::new(start);
+    let mut queue = VecDeque::from([(start, 0)]);
+
+    while let Some((state, cost)) = queue.pop_front() {
+        if space.is_goal(&state) {
+            explored.goals.push(state);
+            break;
+        }
+
+        for (neighbour, _) in space.neighbours(&state) {
+            if explored.costs.contains_key(&neighbour) {
+                continue;
+            }
+
+            explored.costs.insert(neighbour, cost + 1);
+            explored.predecessors.insert(neighbour, vec![state]);
+            queue.push_back((neighbour, cost + 1));
+        }
+    }
+
+    explored
+}
+
+pub fn dijkstra<G: SearchSpace>(space: &G, start: G::State) -> Explored<G::State> {
+    best_first_search(space, start, false, false)
+}
+
+/// Dijkstra that keeps every predecessor leading to a state with the lowest cost and every goal
+/// reached with the lowest cost, so that all shortest paths can be walked back.
+pub fn dijkstra_all_paths<G: SearchSpace>(space: &G, start: G::State) -> Explored<G::State> {
+    best_first_search(space, start, false, true)
+}
+
+pub fn a_star<G: SearchSpace>(space: &G, start: G::State) -> Explored<G::State> {
+    best_first_search(space, start, true, false)
+}
+
+fn best_first_search<G: SearchSpace>(
+    space: &G,
+    start: G::State,
+    use_heuristic: bool,
+    all_paths: bool,
+) -> Explored<G::State> {
+    let priority = |state: &G::State, cost: u64| {
+        if use_heuristic {
+            cost.saturating_add(space.heuristic(state))
+        } else {
+            cost
+        }
+    };
+
+    let mut explored = Explored::new(start);
+    let mut closed = FnvHashSet::default();
+    let mut queue = BinaryHeap::from([QueueEntry {
+        priority: priority(&start, 0),
+        cost: 0,
+        state: start,
+    }]);
+
+    while let Some(QueueEntry { cost, state, .. }) = queue.pop() {
+        if explored
+            .goal_cost()
+            .is_some_and(|goal_cost| goal_cost < cost)
+        {
+            break;
+        }
+
+        if !closed.insert(state) {
+            continue;
+        }
+
+        if space.is_goal(&state) {
+            explored.goals.push(state);
+            if all_paths {
+                continue;
+            }
+
+            break;
+        }
+
+        for (neighbour, step_cost) in space.neighbours(&state) {
+            let neighbour_cost = cost + step_cost;
+            match explored.costs.get(&neighbour) {
+                Some(&known) if known < neighbour_cost => continue,
+                Some(&known) if known == neighbour_cost => {
+                    if all_paths {
+                        explored
+                            .predecessors
+                            .entry(neighbour)
+                            .or_default()
+                            .push(state);
+                    }
+
+                    continue;
+                }
+                _ => (),
+            }
+
+            explored.costs.insert(neighbour, neighbour_cost);
+            explored.predecessors.insert(neighbour, vec![state]);
+            queue.push(QueueEntry {
+                priority: priority(&neighbour, neighbour_cost),
+                cost: neighbour_cost,
+                state: neighbour,
+            });
+        }
+    }
+
+    explored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Line of states `0..length` where stepping by 2 costs 3 and stepping by 1 costs 1.
+    struct Line {
+        length: u64,
+    }
+
+    impl SearchSpace for Line {
+        type State = u64;
+
+        fn neighbours(&self, state: &u64) -> impl IntoIterator<Item = (u64, u64)> {
+            [(state + 1, 1), (state + 2, 3)]
+                .into_iter()
+                .filter(|(next, _)| *next < self.length)
+        }
+
+        fn is_goal(&self, state: &u64) -> bool {
+            *state == self.length - 1
+        }
+
+        fn heuristic(&self, state: &u64) -> u64 {
+            self.length - 1 - state
+        }
+    }
+
+    #[test]
+    fn bfs_counts_steps() {
+        let explored = bfs(&Line { length: 6 }, 0);
+        assert_eq!(explored.goal_cost(), Some(3));
+        assert_eq!(explored.shortest_path().unwrap().0, [0, 1, 3, 5]);
+    }
+
+    #[test]
+    fn dijkstra_and_a_star_use_costs() {
+        let line = Line { length: 6 };
+        for explored in [dijkstra(&line, 0), a_star(&line, 0)] {
+            assert_eq!(explored.shortest_path(), Some((vec![0, 1, 2, 3, 4, 5], 5)));
+        }
+    }
+
+    /// Diamond `0 -> {1, 2} -> 3` where both branches have the same cost.
+    struct Diamond;
+
+    impl SearchSpace for Diamond {
+        type State = u8;
+
+        fn neighbours(&self, state: &u8) -> impl IntoIterator<Item = (u8, u64)> {
+            match state {
+                0 => vec![(1, 1), (2, 1)],
+                1 | 2 => vec![(3, 1)],
+                _ => vec![],
+            }
+        }
+
+        fn is_goal(&self, state: &u8) -> bool {
+            *state == 3
+        }
+    }
+
+    #[test]
+    fn all_shortest_paths() {
+        let explored = dijkstra_all_paths(&Diamond, 0);
+        assert_eq!(explored.goal_cost(), Some(2));
+        assert_eq!(explored.states_on_paths(explored.goals.clone()).len(), 4);
+
+        let explored = dijkstra(&Diamond, 0);
+        assert_eq!(explored.states_on_paths(explored.goals.clone()).len(), 3);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    any::Any,
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
+
+use aoc_runner::{ArcStr, Runner};
+
+use crate::*;
+
+type FactoryFn = fn(ArcStr) -> Result<Box<dyn Runner>, Box<dyn Error>>;
+
+/// A `#[aoc]` solver registered through `aoc_lib!`.
+#[derive(Clone, Copy)]
+pub struct Solver {
+    pub day: u8,
+    pub part: u8,
+    factory: FactoryFn,
+}
+
+impl std::fmt::Debug for Solver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Solver")
+            .field("day", &self.day)
+            .field("part", &self.part)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    Parse(Box<dyn Error>),
+    Solve(Box<dyn Error>),
+    Panic(String),
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(error) => write!(f, "Could not parse input: {error}"),
+            Self::Solve(error) => write!(f, "Could not solve: {error}"),
+            Self::Panic(message) => write!(f, "Solver panicked: {message}"),
+        }
+    }
+}
+
+impl Error for RunError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub answer: String,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+}
+
+impl Solver {
+    /// Parses `input` with the generator of the day and runs the solver on it. Days that have to
+    /// borrow from the input parse it in the solver instead and report errors as `Solve`. A
+    /// panicking solver is reported as an error as well.
+    pub fn run(&self, input: &str) -> Result<Outcome, RunError> {
+        let start = Instant::now();
+        let runner = panic::catch_unwind(|| (self.factory)(ArcStr::from(input)))
+            .map_err(panic_error)?
+            .map_err(RunError::Parse)?;
+        let parsed = Instant::now();
+        let answer = panic::catch_unwind(AssertUnwindSafe(|| runner.try_run()))
+            .map_err(panic_error)?
+            .map_err(RunError::Solve)?
+            .to_string();
+
+        Ok(Outcome {
+            answer,
+            parse_time: parsed - start,
+            solve_time: parsed.elapsed(),
+        })
+    }
+
+    /// Runs with `parameters` (`key=value`) added to the parameters line of the input, taking
+    /// precedence over the ones already there. Only some days read parameters, see
+    /// [`Parameters`](crate::parse::Parameters).
+    pub fn run_with_parameters(
+        &self,
+        input: &str,
+        parameters: &[String],
+    ) -> Result<Outcome, RunError> {
+        if parameters.is_empty() {
+            return self.run(input);
+        }
+
+        let parameters = parameters.join(" ");
+        let input = match input.strip_prefix('#') {
+            Some(rest) => format!("# {parameters} {rest}"),
+            None => format!("# {parameters}\n{input}"),
+        };
+
+        self.run(&input)
+    }
+}
+
+fn panic_error(payload: Box<dyn Any + Send>) -> RunError {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+
+    RunError::Panic(message)
+}
+
+macro_rules! solvers {
+    ($($day:literal, $part:literal => $factory:ident :: $function:ident),* $(,)?) => {
+        [$(Solver {
+            day: $day,
+            part: $part,
+            factory: <Factory as $factory>::$function,
+        }),*]
+    };
+}
+
+pub const SOLVERS: [Solver; 49] = solvers![
+    1, 1 => Day1Part1::day1_part1,
+    1, 2 => Day1Part2::day1_part2,
+    2, 1 => Day2Part1::day2_part1,
+    2, 2 => Day2Part2::day2_part2,
+    3, 1 => Day3Part1::day3_part1,
+    3, 2 => Day3Part2::day3_part2,
+    4, 1 => Day4Part1::day4_part1,
+    4, 2 => Day4Part2::day4_part2,
+    5, 1 => Day5Part1::day5_part1,
+    5, 2 => Day5Part2::day5_part2,
+    6, 1 => Day6Part1::day6_part1,
+    6, 2 => Day6Part2::day6_part2,
+    7, 1 => Day7Part1::day7_part1,
+    7, 2 => Day7Part2::day7_part2,
+    8, 1 => Day8Part1::day8_part1,
+    8, 2 => Day8Part2::day8_part2,
+    9, 1 => Day9Part1::day9_part1,
+    9, 2 => Day9Part2::day9_part2,
+    10, 1 => Day10Part1::day10_part1,
+    10, 2 => Day10Part2::day10_part2,
+    11, 1 => Day11Part1::day11_part1,
+    11, 2 => Day11Part2::day11_part2,
+    12, 1 => Day12Part1::day12_part1,
+    12, 2 => Day12Part2::day12_part2,
+    13, 1 => Day13Part1::day13_part1,
+    13, 2 => Day13Part2::day13_part2,
+    14, 1 => Day14Part1::day14_part1,
+    14, 2 => Day14Part2::day14_part2,
+    15, 1 => Day15Part1::day15_part1,
+    15, 2 => Day15Part2::day15_part2,
+    16, 1 => Day16Part1::day16_part1,
+    16, 2 => Day16Part2::day16_part2,
+    17, 1 => Day17Part1::day17_part1,
+    17, 2 => Day17Part2::day17_part2,
+    18, 1 => Day18Part1::day18_part1,
+    18, 2 => Day18Part2::day18_part2,
+    19, 1 => Day19Part1::day19_part1,
+    19, 2 => Day19Part2::day19_part2,
+    20, 1 => Day20Part1::day20_part1,
+    20, 2 => Day20Part2::day20_part2,
+    21, 1 => Day21Part1::day21_part1,
+    21, 2 => Day21Part2::day21_part2,
+    22, 1 => Day22Part1::day22_part1,
+    22, 2 => Day22Part2::day22_part2,
+    23, 1 => Day23Part1::day23_part1,
+    23, 2 => Day23Part2::day23_part2,
+    24, 1 => Day24Part1::day24_part1,
+    24, 2 => Day24Part2::day24_part2,
+    25, 1 => Day25Part1::day25_part1,
+];
+
+pub fn find(day: u8, part: u8) -> Option<&'static Solver> {
+    SOLVERS
+        .iter()
+        .find(|solver| solver.day == day && solver.part == part)
+}
+
+pub fn solvers_for_day(day: u8) -> impl Iterator<Item = &'static Solver> {
+    SOLVERS.iter().filter(move |solver| solver.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        assert_eq!(SOLVERS.iter().map(|solver| solver.day).max(), Some(25));
+        assert_eq!(solvers_for_day(25).count(), 1);
+
+        let outcome = find(1, 1)
+            .unwrap()
+            .run("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n");
+        assert_eq!(outcome.unwrap().answer, "11");
+
+        for (day, input) in [(1, "1 x"), (2, "1 2 x"), (3, "mul(1,99999999999999999999)")] {
+            let error = find(day, 1).unwrap().run(input).unwrap_err();
+            assert!(matches!(error, RunError::Parse(_)), "{error}");
+        }
+
+        let bytes = "5,4\n4,2\n4,5\n3,0\n2,1\n6,3\n2,4\n1,5\n0,6\n3,3\n2,6\n5,1\n1,2";
+        let run = |input: &str, parameters: &[&str]| {
+            let parameters = parameters.iter().map(|&parameter| parameter.to_owned());
+            find(18, 1)
+                .unwrap()
+                .run_with_parameters(input, &parameters.collect::<Vec<_>>())
+                .unwrap()
+                .answer
+        };
+        assert_eq!(run(bytes, &[]), "22");
+        assert_eq!(run(bytes, &["bytes=0", "size=9x8"]), "15");
+        assert_eq!(run(&format!("# bytes=0\n{bytes}"), &["bytes=1"]), "12");
+    }
+
+    #[test]
+    fn every_solution_registered_once() {
+        let mut registered = SOLVERS
+            .iter()
+            .map(|solver| (solver.day, solver.part))
+            .collect::<Vec<_>>();
+        registered.sort_unstable();
+        let duplicates = registered.windows(2).filter(|pair| pair[0] == pair[1]);
+        assert_eq!(duplicates.count(), 0, "{registered:?}");
+
+        // the `#[aoc(dayN, partP)]` solutions of every day module
+        let mut solutions = Vec::new();
+        for day in 1..=25 {
+            let path = format!("{}/src/day{day:02}.rs", env!("CARGO_MANIFEST_DIR"));
+            let source = std::fs::read_to_string(path).unwrap();
+            solutions.extend(source.lines().filter_map(|line| {
+                let (solution_day, part) = line
+                    .trim()
+                    .strip_prefix("#[aoc(day")?
+                    .strip_suffix(")]")?
+                    .split_once(", part")?;
+                Some((solution_day.parse().unwrap(), part.parse().unwrap()))
+            }));
+        }
+        solutions.sort_unstable();
+        assert_eq!(registered, solutions);
+    }
+}
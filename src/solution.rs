@@ -0,0 +1,48 @@
+use crate::parse::ParseError;
+
+/// Solver of a single day, usable without going through `aoc_runner`.
+///
+/// `Input` may borrow from the puzzle input, days without a real parsing step simply keep the
+/// `&str`.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input<'i>;
+    type Part1;
+    type Part2;
+
+    fn parse(input: &str) -> Result<Self::Input<'_>, ParseError>;
+    fn part1(input: &Self::Input<'_>) -> Self::Part1;
+    fn part2(input: &Self::Input<'_>) -> Self::Part2;
+
+    /// Parses `input` and solves both parts.
+    fn solve(input: &str) -> Result<(Self::Part1, Self::Part2), ParseError> {
+        let input = Self::parse(input)?;
+        Ok((Self::part1(&input), Self::part2(&input)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{day01::Day01, day13::Day13, day23::Day23};
+
+    #[test]
+    fn solve_through_trait() {
+        assert_eq!(Day01::DAY, 1);
+        assert_eq!(
+            Day01::solve("3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n"),
+            Ok((11, 31))
+        );
+
+        let machines =
+            Day13::parse("Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=5400")
+                .unwrap();
+        assert_eq!(machines.len(), 1);
+        assert_eq!(Day13::part1(&machines), 280);
+
+        let graph = Day23::parse("ka-co\nco-de\nde-ka\n").unwrap();
+        assert_eq!(Day23::part2(&graph), "co,de,ka");
+        assert!(Day23::parse("ka co").is_err());
+    }
+}
@@ -0,0 +1,19 @@
+//! Helpers shared by the tests of several days.
+
+/// Deterministic pseudo-random numbers (Knuth's MMIX linear congruential generator).
+pub(crate) struct Random(u64);
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Next number in `0..max`.
+    pub(crate) fn below(&mut self, max: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % max
+    }
+}
@@ -0,0 +1,145 @@
+//! Runs every registered solver against the real puzzle inputs in `input/2024/dayN.txt` and
+//! compares the answers with `input/2024/answers.toml`:
+//!
+//! ```toml
+//! [day1]
+//! part1 = 1234
+//! part2 = "some,string"
+//! ```
+//!
+//! Days without an input or an answer are skipped, `AOC_INPUT_DIR` overrides the directory.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use advent_of_code_2024::runner::SOLVERS;
+
+type Answers = BTreeMap<(u8, u8), String>;
+
+/// Parses the small subset of TOML used by the answers file: `[dayN]` tables holding
+/// `partN = value` keys, values being integers or strings.
+fn parse_answers(content: &str) -> Result<Answers, String> {
+    let mut answers = Answers::new();
+    let mut day = None;
+    for (index, line) in content.lines().enumerate() {
+        let error = |reason: &str| format!("answers.toml, line {}: {reason}", index + 1);
+        let line = line.split_once('#').map_or(line, |(line, _)| line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(table) = line.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            let number = table
+                .trim()
+                .strip_prefix("day")
+                .and_then(|number| number.parse().ok())
+                .ok_or_else(|| error("expected a [dayN] table"))?;
+            day = Some(number);
+            continue;
+        }
+
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| error("expected `partN = value`"))?;
+        let part = key
+            .trim()
+            .strip_prefix("part")
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| error("expected a part1 or part2 key"))?;
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        let day = day.ok_or_else(|| error("answer outside of a [dayN] table"))?;
+
+        answers.insert((day, part), value.to_owned());
+    }
+
+    Ok(answers)
+}
+
+fn input_dir() -> PathBuf {
+    std::env::var_os("AOC_INPUT_DIR").map_or_else(
+        || Path::new(env!("CARGO_MANIFEST_DIR")).join("input/2024"),
+        PathBuf::from,
+    )
+}
+
+#[test]
+fn parse_answers_file() {
+    let answers = parse_answers(
+        "# comment\n[day1]\npart1 = 11\npart2 = \"31\" # trailing\n\n[day23]\npart2 = \"co,de\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        answers.into_iter().collect::<Vec<_>>(),
+        [
+            ((1, 1), "11".to_owned()),
+            ((1, 2), "31".to_owned()),
+            ((23, 2), "co,de".to_owned())
+        ]
+    );
+
+    assert!(parse_answers("part1 = 3").is_err());
+    assert!(parse_answers("[one]").is_err());
+}
+
+#[test]
+fn known_answers() {
+    let dir = input_dir();
+    let Ok(content) = std::fs::read_to_string(dir.join("answers.toml")) else {
+        eprintln!("No answers.toml in {}, skipping", dir.display());
+        return;
+    };
+    let answers = parse_answers(&content).unwrap();
+
+    let mut checked = 0;
+    let mut mismatches = Vec::new();
+    for day in 1..=25 {
+        let Ok(input) = std::fs::read_to_string(dir.join(format!("day{day}.txt"))) else {
+            continue;
+        };
+
+        for solver in SOLVERS.iter().filter(|solver| solver.day == day) {
+            let Some(expected) = answers.get(&(solver.day, solver.part)) else {
+                continue;
+            };
+
+            checked += 1;
+            let actual = match solver.run(&input) {
+                Ok(outcome) => outcome.answer,
+                Err(error) => format!("error: {error}"),
+            };
+            if &actual != expected {
+                mismatches.push((solver.day, solver.part, expected.clone(), actual));
+            }
+        }
+    }
+
+    eprintln!("Checked {checked} answers");
+    if mismatches.is_empty() {
+        return;
+    }
+
+    let width = mismatches
+        .iter()
+        .map(|(_, _, expected, _)| expected.len())
+        .max()
+        .unwrap_or(0)
+        .max("expected".len());
+    let mut table = format!(
+        "{:>3} {:>4}  {:<width$}  actual\n",
+        "day", "part", "expected"
+    );
+    for (day, part, expected, actual) in &mismatches {
+        table += &format!("{day:>3} {part:>4}  {expected:<width$}  {actual}\n");
+    }
+
+    panic!(
+        "{} of {checked} answers changed:\n{table}",
+        mismatches.len()
+    );
+}